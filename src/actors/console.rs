@@ -3,6 +3,7 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::protocol::grip::Grip;
 use crate::protocol::Message;
 use super::Actor;
 
@@ -55,7 +56,7 @@ impl Actor for ConsoleActor {
                 // 开始监听控制台消息
                 Ok(Some(Message {
                     from: self.name().to_string(),
-                    to: msg.from,
+                    to: Some(msg.from),
                     content: serde_json::json!({
                         "type": "listenersStarted",
                         "nativeConsoleAPI": true,
@@ -66,7 +67,7 @@ impl Actor for ConsoleActor {
                 // 返回缓存的消息
                 Ok(Some(Message {
                     from: self.name().to_string(),
-                    to: msg.from,
+                    to: Some(msg.from),
                     content: serde_json::json!({
                         "type": "cachedMessages",
                         "messages": self.messages,
@@ -74,14 +75,14 @@ impl Actor for ConsoleActor {
                 }))
             }
             Some("evaluateJS") => {
-                // 处理JavaScript表达式评估
-                if let Some(expr) = msg.content.get("expr").and_then(Value::as_str) {
+                // 处理JavaScript表达式评估；同步求值的回复不带 `type`，以免被当作 `evaluationResult` 事件
+                if let Some(text) = msg.content.get("text").and_then(Value::as_str) {
                     Ok(Some(Message {
                         from: self.name().to_string(),
-                        to: msg.from,
+                        to: Some(msg.from),
                         content: serde_json::json!({
-                            "type": "evaluationResult",
-                            "result": self.evaluate_js(expr)?,
+                            "input": text,
+                            "result": self.evaluate_js(text)?,
                         }),
                     }))
                 } else {
@@ -94,12 +95,9 @@ impl Actor for ConsoleActor {
 }
 
 impl ConsoleActor {
-    fn evaluate_js(&self, expr: &str) -> Result<Value> {
+    fn evaluate_js(&self, _text: &str) -> Result<Grip> {
         // TODO: 实现JavaScript表达式评估
         // 这里需要集成一个JavaScript引擎，比如v8或deno_core
-        Ok(serde_json::json!({
-            "type": "undefined",
-            "value": null
-        }))
+        Ok(Grip::Undefined)
     }
 } 
\ No newline at end of file
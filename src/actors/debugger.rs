@@ -1,33 +1,212 @@
 use async_trait::async_trait;
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::protocol::grip::Grip;
+use crate::protocol::messages::{PauseReason, ResumeLimit, SourceForm, ThreadOptions};
+use crate::protocol::thread::{Breakpoint, BreakpointLocation, Environment, Frame, Location};
 use crate::protocol::Message;
-use super::Actor;
+use super::source::SourceActor;
+use super::{Actor, ActorRegistry, EventSender, WeakActorRegistry};
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Location {
-    pub line: u32,
-    pub column: u32,
-    pub source_id: String,
+/// 宿主运行时提供的表达式求值，用于断点条件和日志点
+pub trait ExpressionEvaluator: Send + Sync {
+    /// 在 `frame` 的作用域中求值，抛出异常时返回异常信息
+    fn evaluate(&self, frame: Option<&FrameState>, expression: &str) -> std::result::Result<Grip, String>;
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Breakpoint {
-    pub id: String,
-    pub location: Location,
-    pub condition: Option<String>,
-    pub enabled: bool,
+impl<F> ExpressionEvaluator for F
+where
+    F: Fn(Option<&FrameState>, &str) -> std::result::Result<Grip, String> + Send + Sync,
+{
+    fn evaluate(&self, frame: Option<&FrameState>, expression: &str) -> std::result::Result<Grip, String> {
+        self(frame, expression)
+    }
+}
+
+/// 宿主运行时的执行引擎，暂停后的恢复和单步执行委托给它
+///
+/// 方法在持有debugger actor的锁时被调用，不能在其中同步地通过registry访问该actor；
+/// 继续运行的宿主应在之后自己的任务中调用 `hit_breakpoint`、`throw_exception` 或 `pause` 报告暂停。
+pub trait DebuggeeRuntime: Send + Sync {
+    /// 从暂停状态恢复执行，`frames` 为暂停时的调用栈，栈顶在前
+    fn resume(&self, limit: Option<ResumeLimit>, frames: &[FrameState]) -> std::result::Result<ResumeOutcome, String>;
+
+    /// 从头重新执行 `frames[index]`，丢弃其上方的帧
+    fn restart_frame(&self, index: usize, frames: &[FrameState]) -> std::result::Result<ResumeOutcome, String> {
+        let _ = (index, frames);
+        Err("restarting frames is not supported".to_string())
+    }
+}
+
+/// `DebuggeeRuntime` 恢复执行后的状态
+#[derive(Debug, Clone)]
+pub enum ResumeOutcome {
+    /// 执行到单步限制处停下，以 `resumeLimit` 原因暂停在新的调用栈上
+    Paused(Vec<FrameState>),
+    /// 继续运行
+    Running,
+}
+
+/// 执行到断点位置后的处理结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakpointHit {
+    /// 该位置没有断点
+    NoBreakpoint,
+    /// 条件为假，继续执行
+    Skipped,
+    /// 日志点输出了一条控制台消息，继续执行
+    Logged,
+    /// 已暂停
+    Paused,
+}
+
+/// 暂停时的一帧及其作用域链
+#[derive(Debug, Clone)]
+pub struct FrameState {
+    pub frame: Frame,
+    pub environment: Option<Environment>,
 }
 
+/// 暂停期间注册的frame actor，回答 `getEnvironment`
 #[derive(Debug)]
+pub struct FrameActor {
+    state: FrameState,
+}
+
+#[async_trait]
+impl Actor for FrameActor {
+    fn name(&self) -> &str {
+        &self.state.frame.actor
+    }
+
+    fn type_name(&self) -> &str {
+        "frame"
+    }
+
+    fn releasable(&self) -> bool {
+        true
+    }
+
+    async fn handle_message(&mut self, msg: Message) -> Result<Option<Message>> {
+        match msg.content.get("type").and_then(Value::as_str) {
+            Some("getEnvironment") => {
+                let content = match &self.state.environment {
+                    Some(environment) => serde_json::to_value(environment)?,
+                    None => serde_json::json!({
+                        "error": "noEnvironment",
+                        "message": format!("frame {} has no environment", self.name()),
+                    }),
+                };
+                Ok(Some(Message {
+                    from: self.name().to_string(),
+                    to: Some(msg.from),
+                    content,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// 代表一个断点的actor，名称出现在 `setBreakpoint` 的回复和 `paused` 事件的 `why.actors` 中
+///
+/// 收到 `delete` 时从所属的debugger actor中移除断点。
+pub struct BreakpointActor {
+    name: String,
+    thread: String,
+    location: BreakpointLocation,
+    registry: Option<WeakActorRegistry>,
+}
+
+#[async_trait]
+impl Actor for BreakpointActor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn type_name(&self) -> &str {
+        "breakpoint"
+    }
+
+    fn on_register(&mut self, registry: &ActorRegistry) {
+        self.registry = Some(registry.downgrade());
+    }
+
+    async fn handle_message(&mut self, msg: Message) -> Result<Option<Message>> {
+        if msg.content.get("type").and_then(Value::as_str) != Some("delete") {
+            return Ok(None);
+        }
+        let mut removed = false;
+        if let Some(registry) = self.registry.as_ref().and_then(WeakActorRegistry::upgrade)
+            && let Some(mut debugger) = registry.get::<DebuggerActor>(&self.thread).await
+        {
+            removed = debugger.remove_breakpoint(&self.location).is_some();
+        }
+        let content = if removed {
+            serde_json::json!({})
+        } else {
+            serde_json::json!({
+                "error": "noSuchBreakpoint",
+                "message": format!("breakpoint {} was already removed", self.name),
+            })
+        };
+        Ok(Some(Message {
+            from: self.name.clone(),
+            to: Some(msg.from),
+            content,
+        }))
+    }
+}
+
+/// 已保存的断点及其actor名称
+#[derive(Clone)]
+struct StoredBreakpoint {
+    actor: String,
+    breakpoint: Breakpoint,
+}
+
+/// 已加载的源码及其可以设置断点的位置
+struct LoadedSource {
+    form: SourceForm,
+    positions: BTreeMap<u32, Vec<u32>>,
+}
+
+impl LoadedSource {
+    /// 断点滑动到 `(line, column)` 处或之后最近的可断点位置
+    fn slide(&self, line: u32, column: u32) -> Option<Location> {
+        self.positions
+            .range(line..)
+            .flat_map(|(l, columns)| columns.iter().map(move |c| (*l, *c)))
+            .find(|&(l, c)| l > line || c >= column)
+            .map(|(line, column)| Location {
+                line,
+                column,
+                actor: self.form.actor.clone(),
+            })
+    }
+}
+
 pub struct DebuggerActor {
     name: String,
-    breakpoints: HashMap<String, Breakpoint>,
+    breakpoints: HashMap<BreakpointLocation, StoredBreakpoint>,
+    /// 用于生成断点actor名称
+    next_breakpoint: u64,
     paused: bool,
-    current_frame: Option<Location>,
+    /// 暂停时的调用栈，栈顶在前
+    frames: Vec<FrameState>,
+    sources: Vec<LoadedSource>,
+    hit_counts: HashMap<BreakpointLocation, u64>,
+    options: ThreadOptions,
+    evaluator: Option<Arc<dyn ExpressionEvaluator>>,
+    runtime: Option<Arc<dyn DebuggeeRuntime>>,
+    /// 日志点消息的发送者
+    console_actor: Option<String>,
+    registry: Option<WeakActorRegistry>,
+    events: Option<EventSender>,
 }
 
 impl DebuggerActor {
@@ -35,22 +214,357 @@ impl DebuggerActor {
         Self {
             name,
             breakpoints: HashMap::new(),
+            next_breakpoint: 0,
             paused: false,
-            current_frame: None,
+            frames: Vec::new(),
+            sources: Vec::new(),
+            hit_counts: HashMap::new(),
+            options: ThreadOptions::default(),
+            evaluator: None,
+            runtime: None,
+            console_actor: None,
+            registry: None,
+            events: None,
+        }
+    }
+
+    /// 设置求值断点条件和日志点的宿主运行时
+    pub fn with_evaluator(mut self, evaluator: Arc<dyn ExpressionEvaluator>) -> Self {
+        self.evaluator = Some(evaluator);
+        self
+    }
+
+    /// 设置执行单步和重新执行帧的宿主运行时；未设置时只支持不带 `resumeLimit` 的 `resume`
+    pub fn with_runtime(mut self, runtime: Arc<dyn DebuggeeRuntime>) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// 日志点消息以该console actor的 `consoleAPICall` 事件发出，默认由debugger actor自身发出
+    pub fn with_console(mut self, console_actor: &str) -> Self {
+        self.console_actor = Some(console_actor.to_string());
+        self
+    }
+
+    /// 添加断点并返回其actor名称；相同位置的断点会被替换，actor名称保持不变
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> String {
+        let actor = match self.breakpoints.get(&breakpoint.location) {
+            Some(stored) => stored.actor.clone(),
+            None => {
+                self.next_breakpoint += 1;
+                let actor = format!("{}-breakpoint{}", self.name, self.next_breakpoint);
+                if let Some(registry) = self.registry.as_ref().and_then(WeakActorRegistry::upgrade) {
+                    registry.register(BreakpointActor {
+                        name: actor.clone(),
+                        thread: self.name.clone(),
+                        location: breakpoint.location.clone(),
+                        registry: None,
+                    });
+                }
+                actor
+            }
+        };
+        let stored = StoredBreakpoint {
+            actor: actor.clone(),
+            breakpoint,
+        };
+        self.breakpoints.insert(stored.breakpoint.location.clone(), stored);
+        actor
+    }
+
+    /// 移除断点并注销其actor
+    pub fn remove_breakpoint(&mut self, location: &BreakpointLocation) -> Option<Breakpoint> {
+        let stored = self.breakpoints.remove(location)?;
+        self.hit_counts.remove(location);
+        if let Some(registry) = self.registry.as_ref().and_then(WeakActorRegistry::upgrade) {
+            registry.remove(&stored.actor);
+        }
+        Some(stored.breakpoint)
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.breakpoints.values().map(|stored| &stored.breakpoint)
+    }
+
+    /// 断点的actor名称
+    pub fn breakpoint_actor(&self, location: &BreakpointLocation) -> Option<&str> {
+        self.breakpoints.get(location).map(|stored| stored.actor.as_str())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 已加载的源码
+    pub fn sources(&self) -> impl Iterator<Item = &SourceForm> {
+        self.sources.iter().map(|source| &source.form)
+    }
+
+    /// 加载一份源码：注册source actor并推送 `newSource` 事件，返回actor名称
+    ///
+    /// URL匹配的已有断点会自动作用于新源码。
+    pub fn add_source(&mut self, source: SourceActor) -> String {
+        let loaded = LoadedSource {
+            form: source.form(),
+            positions: source.breakpoint_positions(),
+        };
+        if let Some(registry) = self.registry.as_ref().and_then(WeakActorRegistry::upgrade) {
+            registry.register(source);
+        }
+        self.emit(serde_json::json!({ "type": "newSource", "source": loaded.form }));
+        let actor = loaded.form.actor.clone();
+        self.sources.push(loaded);
+        actor
+    }
+
+    /// 断点在各个URL匹配的源码中实际生效的位置，`actor` 为source actor名称
+    pub fn actual_locations(&self, location: &BreakpointLocation) -> Vec<Location> {
+        self.sources
+            .iter()
+            .filter(|source| source.form.url.as_deref() == Some(location.source_url.as_str()))
+            .filter_map(|source| source.slide(location.line, location.column.unwrap_or(0)))
+            .collect()
+    }
+
+    /// 校验并添加断点：已加载URL匹配的源码但其中没有可断点位置时返回错误回复
+    fn set_breakpoint(&mut self, to: String, content: &Value) -> Message {
+        let breakpoint: Breakpoint = match serde_json::from_value(content.clone()) {
+            Ok(breakpoint) => breakpoint,
+            Err(e) => return self.error(to, "invalidBreakpoint", &e.to_string()),
+        };
+        let actual = self.actual_locations(&breakpoint.location);
+        let url = &breakpoint.location.source_url;
+        if actual.is_empty() && self.sources().any(|form| form.url.as_ref() == Some(url)) {
+            let message = format!("no breakable position at {}:{}", url, breakpoint.location.line);
+            return self.error(to, "noBreakablePosition", &message);
+        }
+        let actor = self.add_breakpoint(breakpoint);
+        Message {
+            from: self.name.clone(),
+            to: Some(to),
+            content: serde_json::json!({ "actor": actor, "actualLocations": actual }),
+        }
+    }
+
+    /// 暂停时的调用栈，栈顶在前
+    pub fn frames(&self) -> impl Iterator<Item = &Frame> {
+        self.frames.iter().map(|state| &state.frame)
+    }
+
+    pub fn options(&self) -> &ThreadOptions {
+        &self.options
+    }
+
+    /// 把请求中出现的线程选项合并到当前配置，未出现的选项保持不变；格式错误时返回错误信息，不做任何修改
+    fn configure(&mut self, options: Option<&Value>) -> std::result::Result<(), String> {
+        let Some(options) = options else {
+            return Ok(());
+        };
+        let Value::Object(updates) = options else {
+            return Err(format!("invalid thread options: expected an object, got {}", options));
+        };
+        let mut merged = serde_json::to_value(&self.options).map_err(|e| e.to_string())?;
+        if let Value::Object(current) = &mut merged {
+            current.extend(updates.iter().map(|(key, value)| (key.clone(), value.clone())));
+        }
+        self.options = serde_json::from_value(merged).map_err(|e| format!("invalid thread options: {}", e))?;
+        Ok(())
+    }
+
+    /// 宿主运行时抛出异常时调用，`caught` 表示异常会被捕获；按线程配置决定是否暂停，返回是否已暂停
+    pub fn throw_exception(&mut self, exception: Grip, caught: bool, frames: Vec<FrameState>) -> bool {
+        if !self.options.pause_on_exceptions || (caught && self.options.ignore_caught_exceptions) {
+            return false;
+        }
+        self.pause(PauseReason::Exception { exception }, frames);
+        true
+    }
+
+    /// 断点被触发的次数；条件为假的命中不计入
+    pub fn hit_count(&self, location: &BreakpointLocation) -> u64 {
+        self.hit_counts.get(location).copied().unwrap_or(0)
+    }
+
+    /// 宿主运行时执行到 `location` 时调用，`frames` 为当前调用栈，栈顶在前
+    ///
+    /// 多个断点滑动到同一位置时依次处理：条件断点先求值条件，条件为假时跳过，求值出错时暂停并报告异常；
+    /// 日志点求值日志表达式并推送控制台消息，不暂停；其余断点的actor名称一起出现在 `why.actors` 中。
+    pub fn hit_breakpoint(&mut self, location: &Location, frames: Vec<FrameState>) -> BreakpointHit {
+        let mut matching: Vec<_> = self
+            .breakpoints
+            .values()
+            .filter(|stored| self.actual_locations(&stored.breakpoint.location).contains(location))
+            .cloned()
+            .collect();
+        if matching.is_empty() {
+            return BreakpointHit::NoBreakpoint;
+        }
+        matching.sort_by(|a, b| a.actor.cmp(&b.actor));
+        let mut hit = BreakpointHit::Skipped;
+        let mut actors = Vec::new();
+        for StoredBreakpoint { actor, breakpoint } in matching {
+            if let Some(condition) = &breakpoint.options.condition {
+                match self.evaluate(frames.first(), condition) {
+                    Ok(value) if !value.is_truthy() => continue,
+                    Ok(_) => {}
+                    Err(message) => {
+                        *self.hit_counts.entry(breakpoint.location).or_default() += 1;
+                        let why = PauseReason::BreakpointConditionThrown { message: Some(message) };
+                        self.pause(why, frames);
+                        return BreakpointHit::Paused;
+                    }
+                }
+            }
+            *self.hit_counts.entry(breakpoint.location.clone()).or_default() += 1;
+            if let Some(log_value) = &breakpoint.options.log_value {
+                let (level, argument) = match self.evaluate(frames.first(), log_value) {
+                    Ok(value) => ("logPoint", value),
+                    Err(message) => ("logPointError", Grip::String(message)),
+                };
+                self.log(&breakpoint.location, location, level, argument);
+                hit = BreakpointHit::Logged;
+                continue;
+            }
+            actors.push(actor);
+        }
+        if actors.is_empty() {
+            return hit;
+        }
+        self.pause(PauseReason::Breakpoint { actors }, frames);
+        BreakpointHit::Paused
+    }
+
+    fn evaluate(&self, frame: Option<&FrameState>, expression: &str) -> std::result::Result<Grip, String> {
+        match &self.evaluator {
+            Some(evaluator) => evaluator.evaluate(frame, expression),
+            None => Err("no runtime to evaluate breakpoint expressions".to_string()),
+        }
+    }
+
+    /// 以 `consoleAPICall` 事件推送日志点消息
+    fn log(&self, breakpoint: &BreakpointLocation, location: &Location, level: &str, argument: Grip) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        let time_stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        events.emit(Message {
+            from: self.console_actor.clone().unwrap_or_else(|| self.name.clone()),
+            to: None,
+            content: serde_json::json!({
+                "type": "consoleAPICall",
+                "message": {
+                    "level": level,
+                    "arguments": [argument],
+                    "filename": breakpoint.source_url,
+                    "lineNumber": location.line,
+                    "columnNumber": location.column,
+                    "timeStamp": time_stamp,
+                },
+            }),
+        });
+    }
+
+    /// 以给定的调用栈进入暂停状态：为每一帧注册frame actor，并推送 `paused` 事件
+    pub fn pause(&mut self, why: PauseReason, frames: Vec<FrameState>) {
+        self.release_frames();
+        if let Some(registry) = self.registry.as_ref().and_then(WeakActorRegistry::upgrade) {
+            for state in &frames {
+                registry.register(FrameActor { state: state.clone() });
+            }
+        }
+        self.paused = true;
+        self.frames = frames;
+        let mut event = serde_json::json!({
+            "type": "paused",
+            "why": why,
+        });
+        if let Some(top) = self.frames.first() {
+            event["frame"] = serde_json::to_value(&top.frame).unwrap_or_default();
+        }
+        self.emit(event);
+    }
+
+    /// 恢复执行：注销frame actors，并推送 `resumed` 事件
+    pub fn resume(&mut self) {
+        self.release_frames();
+        self.paused = false;
+        self.emit(serde_json::json!({ "type": "resumed" }));
+    }
+
+    /// 处理 `resume` 请求：不带限制时直接恢复，否则交给宿主运行时单步或重新执行帧
+    fn resume_with_limit(&mut self, to: String, content: &Value) -> Message {
+        if !self.paused {
+            return self.not_paused(to);
+        }
+        let limit = match content.get("resumeLimit").filter(|limit| !limit.is_null()) {
+            Some(limit) => match serde_json::from_value::<ResumeLimit>(limit.clone()) {
+                Ok(limit) => Some(limit),
+                Err(e) => return self.error(to, "invalidResumeLimit", &e.to_string()),
+            },
+            None => None,
+        };
+        let outcome = match (&self.runtime, limit) {
+            (None, None) => Ok(ResumeOutcome::Running),
+            (None, Some(_)) => return self.error(to, "noRuntime", "no runtime to step the debuggee"),
+            (Some(runtime), Some(ResumeLimit::Restart)) => {
+                let frame = content.get("frameActorID").and_then(Value::as_str);
+                let Some(index) = self.frames.iter().position(|state| Some(state.frame.actor.as_str()) == frame) else {
+                    return self.error(to, "unknownFrame", &format!("no frame {}", frame.unwrap_or_default()));
+                };
+                runtime.restart_frame(index, &self.frames)
+            }
+            (Some(runtime), limit) => runtime.resume(limit, &self.frames),
+        };
+        match outcome {
+            Ok(outcome) => {
+                self.resume();
+                if let ResumeOutcome::Paused(frames) = outcome {
+                    self.pause(PauseReason::ResumeLimit, frames);
+                }
+                Message {
+                    from: self.name.clone(),
+                    to: Some(to),
+                    content: serde_json::json!({}),
+                }
+            }
+            Err(message) => self.error(to, "runtimeError", &message),
+        }
+    }
+
+    fn release_frames(&mut self) {
+        if let Some(registry) = self.registry.as_ref().and_then(WeakActorRegistry::upgrade) {
+            for state in &self.frames {
+                registry.remove(&state.frame.actor);
+            }
         }
+        self.frames.clear();
     }
 
-    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
-        self.breakpoints.insert(breakpoint.id.clone(), breakpoint);
+    fn emit(&self, content: Value) {
+        if let Some(events) = &self.events {
+            events.emit(Message {
+                from: self.name.clone(),
+                to: None,
+                content,
+            });
+        }
     }
 
-    pub fn remove_breakpoint(&mut self, id: &str) -> Option<Breakpoint> {
-        self.breakpoints.remove(id)
+    fn error(&self, to: String, error: &str, message: &str) -> Message {
+        Message {
+            from: self.name.clone(),
+            to: Some(to),
+            content: serde_json::json!({
+                "error": error,
+                "message": message,
+            }),
+        }
     }
 
-    pub fn set_paused(&mut self, paused: bool, location: Option<Location>) {
-        self.paused = paused;
-        self.current_frame = location;
+    fn not_paused(&self, to: String) -> Message {
+        self.error(to, "wrongState", "thread is not paused")
     }
 }
 
@@ -64,107 +578,95 @@ impl Actor for DebuggerActor {
         "debugger"
     }
 
+    fn on_register(&mut self, registry: &ActorRegistry) {
+        self.registry = Some(registry.downgrade());
+        self.events = Some(registry.event_sender());
+    }
+
     async fn handle_message(&mut self, msg: Message) -> Result<Option<Message>> {
         match msg.content.get("type").and_then(Value::as_str) {
-            Some("setBreakpoint") => {
-                if let Ok(breakpoint) = serde_json::from_value(msg.content.get("breakpoint").unwrap_or(&Value::Null).clone()) {
-                    self.add_breakpoint(breakpoint);
-                    Ok(Some(Message {
-                        from: self.name().to_string(),
-                        to: msg.from,
-                        content: serde_json::json!({
-                            "type": "breakpointAdded",
-                            "breakpoints": self.breakpoints.values().collect::<Vec<_>>(),
-                        }),
-                    }))
-                } else {
-                    Ok(None)
-                }
-            }
-            Some("removeBreakpoint") => {
-                if let Some(id) = msg.content.get("id").and_then(Value::as_str) {
-                    if let Some(breakpoint) = self.remove_breakpoint(id) {
-                        Ok(Some(Message {
-                            from: self.name().to_string(),
-                            to: msg.from,
-                            content: serde_json::json!({
-                                "type": "breakpointRemoved",
-                                "id": breakpoint.id,
-                            }),
-                        }))
-                    } else {
-                        Ok(None)
-                    }
-                } else {
-                    Ok(None)
+            Some("attach") | Some("detach") => {
+                if let Err(message) = self.configure(msg.content.get("options")) {
+                    return Ok(Some(self.error(msg.from, "wrongParameter", &message)));
                 }
-            }
-            Some("pause") => {
-                self.set_paused(true, None);
+                self.release_frames();
+                self.paused = false;
                 Ok(Some(Message {
                     from: self.name().to_string(),
-                    to: msg.from,
-                    content: serde_json::json!({
-                        "type": "paused",
-                        "why": { "type": "clientRequest" },
-                    }),
+                    to: Some(msg.from),
+                    content: serde_json::json!({}),
                 }))
             }
-            Some("resume") => {
-                self.set_paused(false, None);
+            Some("reconfigure") => {
+                if let Err(message) = self.configure(msg.content.get("options")) {
+                    return Ok(Some(self.error(msg.from, "wrongParameter", &message)));
+                }
                 Ok(Some(Message {
                     from: self.name().to_string(),
-                    to: msg.from,
-                    content: serde_json::json!({
-                        "type": "resumed",
-                    }),
+                    to: Some(msg.from),
+                    content: serde_json::json!({}),
                 }))
             }
-            Some("stepOver") => {
-                // TODO: 实现单步执行
+            Some("pauseOnExceptions") => {
+                // 旧版协议直接在请求中携带选项
+                if let Err(message) = self.configure(Some(&msg.content)) {
+                    return Ok(Some(self.error(msg.from, "wrongParameter", &message)));
+                }
                 Ok(Some(Message {
                     from: self.name().to_string(),
-                    to: msg.from,
-                    content: serde_json::json!({
-                        "type": "stepped",
-                        "why": { "type": "stepOver" },
-                    }),
+                    to: Some(msg.from),
+                    content: serde_json::json!({}),
                 }))
             }
-            Some("stepIn") => {
-                // TODO: 实现步入
+            Some("setBreakpoint") => Ok(Some(self.set_breakpoint(msg.from, &msg.content))),
+            Some("removeBreakpoint") => {
+                let location = msg
+                    .content
+                    .get("location")
+                    .and_then(|location| serde_json::from_value::<BreakpointLocation>(location.clone()).ok());
+                let removed = location.and_then(|location| self.remove_breakpoint(&location));
+                if removed.is_none() {
+                    return Ok(Some(self.error(msg.from, "noSuchBreakpoint", "no breakpoint at this location")));
+                }
                 Ok(Some(Message {
                     from: self.name().to_string(),
-                    to: msg.from,
-                    content: serde_json::json!({
-                        "type": "stepped",
-                        "why": { "type": "stepIn" },
-                    }),
+                    to: Some(msg.from),
+                    content: serde_json::json!({}),
                 }))
             }
-            Some("stepOut") => {
-                // TODO: 实现步出
+            Some("interrupt") | Some("pause") => {
+                // 暂停通过 `paused` 事件通知，回复本身不带类型
+                self.pause(PauseReason::Interrupted { on_next: false }, Vec::new());
                 Ok(Some(Message {
                     from: self.name().to_string(),
-                    to: msg.from,
-                    content: serde_json::json!({
-                        "type": "stepped",
-                        "why": { "type": "stepOut" },
-                    }),
+                    to: Some(msg.from),
+                    content: serde_json::json!({}),
                 }))
             }
+            Some("resume") => Ok(Some(self.resume_with_limit(msg.from, &msg.content))),
+            Some("sources") => Ok(Some(Message {
+                from: self.name().to_string(),
+                to: Some(msg.from),
+                content: serde_json::json!({ "sources": self.sources().collect::<Vec<_>>() }),
+            })),
             Some("frames") => {
-                // TODO: 实现调用栈帧获取
+                if !self.paused {
+                    return Ok(Some(self.not_paused(msg.from)));
+                }
+                let start = msg.content.get("start").and_then(Value::as_u64).unwrap_or(0) as usize;
+                let count = msg
+                    .content
+                    .get("count")
+                    .and_then(Value::as_u64)
+                    .map_or(usize::MAX, |count| count as usize);
+                let frames: Vec<_> = self.frames().skip(start).take(count).collect();
                 Ok(Some(Message {
                     from: self.name().to_string(),
-                    to: msg.from,
-                    content: serde_json::json!({
-                        "type": "frames",
-                        "frames": [],
-                    }),
+                    to: Some(msg.from),
+                    content: serde_json::json!({ "frames": frames }),
                 }))
             }
             _ => Ok(None),
         }
     }
-} 
\ No newline at end of file
+}
@@ -1,13 +1,37 @@
 use async_trait::async_trait;
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex, Weak};
+use tokio::sync::{broadcast, Mutex, OwnedMappedMutexGuard, OwnedMutexGuard};
 
 use crate::protocol::Message;
 
+pub mod console;
+pub mod debugger;
+pub mod network;
+pub mod source;
+
+/// 提供向具体actor类型转换的能力，所有 `'static` 类型自动实现
+pub trait AsAny: Any {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
 /// Actor trait定义了所有actors必须实现的基本功能
 #[async_trait]
-pub trait Actor {
+pub trait Actor: AsAny + Send {
     /// 获取actor的名称
     fn name(&self) -> &str;
     
@@ -16,23 +40,256 @@ pub trait Actor {
     
     /// 处理接收到的消息
     async fn handle_message(&mut self, msg: Message) -> Result<Option<Message>>;
+
+    /// 注册到注册表时调用，actor可以在此保存事件发送端等注册表资源
+    fn on_register(&mut self, _registry: &ActorRegistry) {}
+
+    /// 是否属于对象、长字符串、帧等actor池，只有这类actor可以被客户端的 `release` 请求释放
+    fn releasable(&self) -> bool {
+        false
+    }
+}
+
+/// 共享的actor句柄
+pub type SharedActor = Arc<Mutex<dyn Actor>>;
+
+/// 注册表中的一项，记录具体类型和能否释放，以便不加锁地查询
+struct Entry {
+    type_id: TypeId,
+    releasable: bool,
+    actor: SharedActor,
+}
+
+type ActorMap = HashMap<String, Entry>;
+
+/// 每个连接缓冲的待发送事件数量
+const EVENT_BUFFER: usize = 256;
+
+/// actors推送事件的发送端，事件会发送给注册表的所有连接
+#[derive(Clone, Debug)]
+pub struct EventSender {
+    tx: broadcast::Sender<Message>,
+}
+
+impl EventSender {
+    /// 推送事件；没有连接时事件被丢弃
+    pub fn emit(&self, event: Message) {
+        let _ = self.tx.send(event);
+    }
+}
+
+/// Actor注册表：按名称保存共享的actor，可在多个任务间克隆使用
+#[derive(Clone)]
+pub struct ActorRegistry {
+    actors: Arc<StdMutex<ActorMap>>,
+    events: broadcast::Sender<Message>,
+}
+
+impl Default for ActorRegistry {
+    fn default() -> Self {
+        Self {
+            actors: Arc::default(),
+            events: broadcast::channel(EVENT_BUFFER).0,
+        }
+    }
+}
+
+impl ActorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建不持有注册表的弱引用，供注册表中的actor反向访问注册表
+    pub fn downgrade(&self) -> WeakActorRegistry {
+        WeakActorRegistry {
+            actors: Arc::downgrade(&self.actors),
+            events: self.events.clone(),
+        }
+    }
+
+    /// actors推送事件使用的发送端
+    pub fn event_sender(&self) -> EventSender {
+        EventSender {
+            tx: self.events.clone(),
+        }
+    }
+
+    /// 订阅actors推送的事件
+    pub fn subscribe_events(&self) -> broadcast::Receiver<Message> {
+        self.events.subscribe()
+    }
+
+    /// 注册actor，返回其名称；同名actor会被替换
+    pub fn register<A: Actor>(&self, mut actor: A) -> String {
+        actor.on_register(self);
+        let name = actor.name().to_string();
+        let entry = Entry {
+            type_id: TypeId::of::<A>(),
+            releasable: actor.releasable(),
+            actor: Arc::new(Mutex::new(actor)),
+        };
+        self.actors.lock().unwrap().insert(name.clone(), entry);
+        name
+    }
+
+    /// 按名称查找actor
+    pub fn find(&self, name: &str) -> Option<SharedActor> {
+        self.actors.lock().unwrap().get(name).map(|entry| entry.actor.clone())
+    }
+
+    /// 按名称获取指定类型的actor，类型不匹配时返回 `None`
+    pub async fn get<A: Actor>(&self, name: &str) -> Option<OwnedMappedMutexGuard<dyn Actor, A>> {
+        let guard = self.find(name)?.lock_owned().await;
+        OwnedMutexGuard::try_map(guard, |actor| actor.as_any_mut().downcast_mut::<A>()).ok()
+    }
+
+    /// 移除actor
+    pub fn remove(&self, name: &str) -> Option<SharedActor> {
+        self.actors.lock().unwrap().remove(name).map(|entry| entry.actor)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.actors.lock().unwrap().contains_key(name)
+    }
+
+    /// 处理 `release` 请求，返回回复内容；actor不存在时返回 `None`
+    fn release(&self, name: &str) -> Option<Value> {
+        let mut actors = self.actors.lock().unwrap();
+        if !actors.get(name)?.releasable {
+            return Some(serde_json::json!({
+                "error": "cannotRelease",
+                "message": format!("actor {} is not pool-scoped and cannot be released", name),
+            }));
+        }
+        actors.remove(name);
+        Some(serde_json::json!({}))
+    }
+
+    /// 所有已注册actor的名称
+    pub fn names(&self) -> Vec<String> {
+        self.actors.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// 类型为 `A` 的actor名称，按名称排序；不会锁定任何actor
+    pub fn names_of<A: Actor>(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .actors
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, entry)| entry.type_id == TypeId::of::<A>())
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// 将消息分发给 `to` 指定的actor
+    ///
+    /// `release` 请求只会移除可释放的actor；所有连接共享注册表，root、tab、thread等actor收到时回复错误。
+    pub async fn handle_message(&self, msg: Message) -> Result<Option<Message>> {
+        let Some(to) = msg.to.clone() else {
+            return Ok(None);
+        };
+        if msg.packet_type() == Some("release") {
+            return Ok(self.release(&to).map(|content| Message {
+                from: to,
+                to: None,
+                content,
+            }));
+        }
+        let Some(actor) = self.find(&to) else {
+            return Ok(None);
+        };
+        let mut actor = actor.lock().await;
+        actor.handle_message(msg).await
+    }
+}
+
+/// 注册表的弱引用，避免actor与注册表之间形成引用环
+#[derive(Clone)]
+pub struct WeakActorRegistry {
+    actors: Weak<StdMutex<ActorMap>>,
+    events: broadcast::Sender<Message>,
+}
+
+impl WeakActorRegistry {
+    pub fn upgrade(&self) -> Option<ActorRegistry> {
+        self.actors.upgrade().map(|actors| ActorRegistry {
+            actors,
+            events: self.events.clone(),
+        })
+    }
 }
 
 /// Root Actor实现
 pub mod root {
     use super::*;
 
-    #[derive(Debug)]
+    use super::tab::TabActor;
+
     pub struct RootActor {
         name: String,
+        application_type: String,
+        traits: Value,
+        registry: Option<WeakActorRegistry>,
     }
 
     impl RootActor {
         pub fn new() -> Self {
+            Self::with_traits("browser".to_string(), serde_json::json!({}))
+        }
+
+        /// 指定应用类型和特性创建root actor，二者会出现在连接时的hello数据包中
+        pub fn with_traits(application_type: String, traits: Value) -> Self {
             Self {
                 name: "root".to_string(),
+                application_type,
+                traits,
+                registry: None,
             }
         }
+
+        /// 连接建立后服务器发送的第一个数据包
+        pub fn hello(&self) -> Message {
+            Message {
+                from: self.name.clone(),
+                to: None,
+                content: serde_json::json!({
+                    "applicationType": self.application_type,
+                    "traits": self.traits,
+                }),
+            }
+        }
+
+        /// 注册表中所有tab actors的描述，按名称排序
+        async fn tabs(&self) -> Vec<Value> {
+            let Some(registry) = self.registry.as_ref().and_then(WeakActorRegistry::upgrade) else {
+                return Vec::new();
+            };
+            let mut tabs = Vec::new();
+            // 只锁定tab actors，不会等待正在处理消息的其他actor
+            for name in registry.names_of::<TabActor>() {
+                if let Some(tab) = registry.get::<TabActor>(&name).await {
+                    tabs.push(tab.form());
+                }
+            }
+            tabs
+        }
+
+        fn reply(&self, content: Value) -> Message {
+            Message {
+                from: self.name.clone(),
+                to: None,
+                content,
+            }
+        }
+    }
+
+    impl Default for RootActor {
+        fn default() -> Self {
+            Self::new()
+        }
     }
 
     #[async_trait]
@@ -45,22 +302,44 @@ pub mod root {
             "root"
         }
 
+        /// `listTabs` 等请求会列出同一注册表中的tab actors
+        fn on_register(&mut self, registry: &ActorRegistry) {
+            self.registry = Some(registry.downgrade());
+        }
+
         async fn handle_message(&mut self, msg: Message) -> Result<Option<Message>> {
             // 处理root actor特定的消息
             match msg.content.get("type").and_then(Value::as_str) {
                 Some("listTabs") => {
-                    // 返回可用的tabs列表
-                    let response = Message {
-                        from: self.name().to_string(),
-                        to: None,
-                        content: serde_json::json!({
-                            "type": "tabList",
-                            "tabs": [],
-                            "selected": 0
-                        }),
+                    // 返回注册表中的tabs列表
+                    let tabs = self.tabs().await;
+                    Ok(Some(self.reply(serde_json::json!({
+                        "tabs": tabs,
+                        "selected": 0
+                    }))))
+                }
+                Some("getTab") => {
+                    let browser_id = msg.content.get("browserId").and_then(Value::as_u64);
+                    let tabs = self.tabs().await;
+                    let tab = match browser_id {
+                        Some(id) => tabs.into_iter().find(|tab| tab["browserId"].as_u64() == Some(id)),
+                        None => tabs.into_iter().next(),
                     };
-                    Ok(Some(response))
+                    Ok(Some(match tab {
+                        Some(tab) => self.reply(serde_json::json!({ "tab": tab })),
+                        None => self.reply(serde_json::json!({
+                            "error": "noTab",
+                            "message": "no tab matches the request",
+                        })),
+                    }))
+                }
+                Some("listProcesses") => Ok(Some(self.reply(serde_json::json!({ "processes": [] })))),
+                Some("listWorkers") => Ok(Some(self.reply(serde_json::json!({ "workers": [] })))),
+                Some("listServiceWorkerRegistrations") => {
+                    Ok(Some(self.reply(serde_json::json!({ "registrations": [] }))))
                 }
+                Some("listAddons") => Ok(Some(self.reply(serde_json::json!({ "addons": [] })))),
+                Some("getRoot") => Ok(Some(self.reply(serde_json::json!({})))),
                 _ => Ok(None),
             }
         }
@@ -71,16 +350,121 @@ pub mod root {
 pub mod tab {
     use super::*;
 
+    use crate::protocol::messages::ReloadOptions;
+
     #[derive(Debug)]
     pub struct TabActor {
         name: String,
         title: String,
         url: String,
+        browser_id: Option<u64>,
+        console_actor: Option<String>,
+        thread_actor: Option<String>,
+        /// 后退、前进历史
+        back: Vec<String>,
+        forward: Vec<String>,
+        events: Option<EventSender>,
     }
 
     impl TabActor {
         pub fn new(name: String, title: String, url: String) -> Self {
-            Self { name, title, url }
+            Self {
+                name,
+                title,
+                url,
+                browser_id: None,
+                console_actor: None,
+                thread_actor: None,
+                back: Vec::new(),
+                forward: Vec::new(),
+                events: None,
+            }
+        }
+
+        /// 设置该tab的console和thread actor，二者需要另行注册，`getTarget` 会在target描述中返回它们
+        pub fn with_actors(mut self, console_actor: String, thread_actor: String) -> Self {
+            self.console_actor = Some(console_actor);
+            self.thread_actor = Some(thread_actor);
+            self
+        }
+
+        /// 设置 `getTab` 请求中用于查找tab的browser id
+        pub fn with_browser_id(mut self, browser_id: u64) -> Self {
+            self.browser_id = Some(browser_id);
+            self
+        }
+
+        /// `listTabs`、`getTab` 回复中的tab描述
+        pub fn form(&self) -> Value {
+            let mut form = serde_json::json!({
+                "actor": self.name,
+                "title": self.title,
+                "url": self.url,
+            });
+            if let Some(browser_id) = self.browser_id {
+                form["browserId"] = browser_id.into();
+            }
+            form
+        }
+
+        pub fn title(&self) -> &str {
+            &self.title
+        }
+
+        pub fn url(&self) -> &str {
+            &self.url
+        }
+
+        /// 加载页面：依次推送 `will-navigate` 和 `tabNavigated` 事件，并更新url和标题
+        ///
+        /// 这里没有真正的页面，标题取页面的url。
+        pub fn navigate(&mut self, url: String) {
+            self.load(url, None);
+        }
+
+        /// 重新加载当前页面，导航事件中带有 `reload: {force}`
+        pub fn reload(&mut self, force: bool) {
+            self.load(self.url.clone(), Some(ReloadOptions { force }));
+        }
+
+        fn load(&mut self, url: String, reload: Option<ReloadOptions>) {
+            let mut will_navigate = serde_json::json!({
+                "type": "will-navigate",
+                "newURI": url,
+            });
+            self.title = url.clone();
+            self.url = url;
+            let mut navigated = serde_json::json!({
+                "type": "tabNavigated",
+                "url": self.url,
+                "title": self.title,
+                "state": "stop",
+                "isFrameSwitching": false,
+            });
+            if let Some(reload) = reload {
+                will_navigate["reload"] = serde_json::json!(reload);
+                navigated["reload"] = serde_json::json!(reload);
+            }
+            self.emit(will_navigate);
+            self.emit(navigated);
+        }
+
+        fn emit(&self, content: Value) {
+            if let Some(events) = &self.events {
+                events.emit(Message {
+                    from: self.name.clone(),
+                    to: None,
+                    content,
+                });
+            }
+        }
+
+        fn reply(&self, content: Value) -> Message {
+            Message {
+                from: self.name.clone(),
+                to: None,
+                content,
+            }
         }
     }
 
@@ -94,20 +478,86 @@ pub mod tab {
             "tab"
         }
 
+        fn on_register(&mut self, registry: &ActorRegistry) {
+            self.events = Some(registry.event_sender());
+        }
+
         async fn handle_message(&mut self, msg: Message) -> Result<Option<Message>> {
             // 处理tab actor特定的消息
             match msg.content.get("type").and_then(Value::as_str) {
-                Some("attach") => {
-                    // 处理attach请求
-                    let response = Message {
+                Some("getTarget") => {
+                    let content = match (&self.console_actor, &self.thread_actor) {
+                        (Some(console_actor), Some(thread_actor)) => serde_json::json!({
+                            "frame": {
+                                "actor": self.name,
+                                "title": self.title,
+                                "url": self.url,
+                                "consoleActor": console_actor,
+                                "threadActor": thread_actor,
+                            },
+                        }),
+                        _ => serde_json::json!({
+                            "error": "noTarget",
+                            "message": format!("tab {} has no console or thread actor", self.name),
+                        }),
+                    };
+                    Ok(Some(Message {
                         from: self.name().to_string(),
                         to: None,
-                        content: serde_json::json!({
+                        content,
+                    }))
+                }
+                Some("navigateTo") => {
+                    let Some(url) = msg.content.get("url").and_then(Value::as_str) else {
+                        return Ok(Some(self.reply(serde_json::json!({
+                            "error": "missingParameter",
+                            "message": "navigateTo requires a url",
+                        }))));
+                    };
+                    self.back.push(self.url.clone());
+                    self.forward.clear();
+                    self.navigate(url.to_string());
+                    Ok(Some(self.reply(serde_json::json!({}))))
+                }
+                Some("reload") => {
+                    let force = msg.content["options"]["force"].as_bool().unwrap_or(false);
+                    self.reload(force);
+                    Ok(Some(self.reply(serde_json::json!({}))))
+                }
+                Some(direction @ ("goBack" | "goForward")) => {
+                    let (from, to) = if direction == "goBack" {
+                        (&mut self.back, &mut self.forward)
+                    } else {
+                        (&mut self.forward, &mut self.back)
+                    };
+                    let Some(url) = from.pop() else {
+                        return Ok(Some(self.reply(serde_json::json!({
+                            "error": "noHistory",
+                            "message": format!("{} has no history entry for {}", self.name, direction),
+                        }))));
+                    };
+                    to.push(self.url.clone());
+                    self.navigate(url);
+                    Ok(Some(self.reply(serde_json::json!({}))))
+                }
+                Some("detach") => Ok(Some(self.reply(serde_json::json!({})))),
+                Some("attach") => {
+                    // 处理旧版的attach请求
+                    let content = match &self.thread_actor {
+                        Some(thread_actor) => serde_json::json!({
                             "type": "attached",
-                            "threadActor": format!("{}-thread", self.name),
+                            "threadActor": thread_actor,
+                        }),
+                        None => serde_json::json!({
+                            "error": "noThread",
+                            "message": format!("tab {} has no thread actor", self.name),
                         }),
                     };
-                    Ok(Some(response))
+                    Ok(Some(Message {
+                        from: self.name().to_string(),
+                        to: None,
+                        content,
+                    }))
                 }
                 _ => Ok(None),
             }
@@ -82,9 +82,20 @@ impl NetworkActor {
         }
     }
 
+    pub fn add_listener(&mut self, listener: String) {
+        if !self.listeners.contains(&listener) {
+            self.listeners.push(listener);
+        }
+    }
+
+    pub fn listeners(&self) -> &[String] {
+        &self.listeners
+    }
+
     pub fn add_request(&mut self, request: NetworkRequest) {
-        self.requests.insert(request.request_id.clone(), request);
-        self.notify_request_started(&request.request_id);
+        let request_id = request.request_id.clone();
+        self.requests.insert(request_id.clone(), request);
+        self.notify_request_started(&request_id);
     }
 
     pub fn update_request(&mut self, request_id: &str, status: u16, status_text: String, 
@@ -96,7 +107,7 @@ impl NetworkActor {
     }
 
     fn notify_request_started(&self, request_id: &str) {
-        if let Some(request) = self.requests.get(request_id) {
+        if self.requests.contains_key(request_id) {
             // 通知所有监听器新请求开始
             for listener in &self.listeners {
                 // TODO: 发送通知
@@ -106,7 +117,7 @@ impl NetworkActor {
     }
 
     fn notify_request_finished(&self, request_id: &str) {
-        if let Some(request) = self.requests.get(request_id) {
+        if self.requests.contains_key(request_id) {
             // 通知所有监听器请求完成
             for listener in &self.listeners {
                 // TODO: 发送通知
@@ -132,7 +143,7 @@ impl Actor for NetworkActor {
                 self.listeners.push(msg.from.clone());
                 Ok(Some(Message {
                     from: self.name().to_string(),
-                    to: msg.from,
+                    to: Some(msg.from),
                     content: serde_json::json!({
                         "type": "listenersStarted"
                     }),
@@ -142,7 +153,7 @@ impl Actor for NetworkActor {
                 self.listeners.retain(|listener| *listener != msg.from);
                 Ok(Some(Message {
                     from: self.name().to_string(),
-                    to: msg.from,
+                    to: Some(msg.from),
                     content: serde_json::json!({
                         "type": "listenersStopped"
                     }),
@@ -153,7 +164,7 @@ impl Actor for NetworkActor {
                     if let Some(request) = self.requests.get(id) {
                         Ok(Some(Message {
                             from: self.name().to_string(),
-                            to: msg.from,
+                            to: Some(msg.from),
                             content: serde_json::json!({
                                 "type": "requestContent",
                                 "id": id,
@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::protocol::messages::{PositionQuery, SourceForm};
+use crate::protocol::Message;
+use super::Actor;
+
+/// 一份已加载的源码，回答 `source`、`getBreakableLines` 和 `getBreakpointPositionsCompressed`
+#[derive(Debug)]
+pub struct SourceActor {
+    name: String,
+    url: Option<String>,
+    text: String,
+    content_type: String,
+}
+
+impl SourceActor {
+    pub fn new(name: String, url: Option<String>, text: String) -> Self {
+        Self {
+            name,
+            url,
+            text,
+            content_type: "text/javascript".to_string(),
+        }
+    }
+
+    pub fn with_content_type(mut self, content_type: &str) -> Self {
+        self.content_type = content_type.to_string();
+        self
+    }
+
+    pub fn form(&self) -> SourceForm {
+        SourceForm {
+            actor: self.name.clone(),
+            url: self.url.clone(),
+            source_map_url: None,
+            is_black_boxed: false,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// 每个非空、非注释行的第一个非空白字符处可以设置断点，行号从1开始，列号从0开始
+    pub fn breakpoint_positions(&self) -> BTreeMap<u32, Vec<u32>> {
+        self.text
+            .lines()
+            .zip(1..)
+            .filter_map(|(content, line)| {
+                let code = content.trim_start();
+                if code.is_empty() || code.starts_with("//") {
+                    return None;
+                }
+                let column = content.chars().count() - code.chars().count();
+                Some((line, vec![column as u32]))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Actor for SourceActor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn type_name(&self) -> &str {
+        "source"
+    }
+
+    async fn handle_message(&mut self, msg: Message) -> Result<Option<Message>> {
+        let content = match msg.content.get("type").and_then(Value::as_str) {
+            Some("source") => serde_json::json!({
+                "source": self.text,
+                "contentType": self.content_type,
+            }),
+            Some("getBreakableLines") => serde_json::json!({
+                "lines": self.breakpoint_positions().into_keys().collect::<Vec<_>>(),
+            }),
+            Some("getBreakpointPositionsCompressed") => {
+                let query: Option<PositionQuery> = msg
+                    .content
+                    .get("query")
+                    .and_then(|query| serde_json::from_value(query.clone()).ok());
+                let mut positions = self.breakpoint_positions();
+                if let Some(query) = query {
+                    let start = (query.start.line, query.start.column);
+                    let end = (query.end.line, query.end.column);
+                    positions.retain(|line, columns| {
+                        columns.retain(|column| (start..end).contains(&(*line, *column)));
+                        !columns.is_empty()
+                    });
+                }
+                serde_json::json!({ "positions": positions })
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(Message {
+            from: self.name().to_string(),
+            to: Some(msg.from),
+            content,
+        }))
+    }
+}
@@ -0,0 +1,55 @@
+//! root actor上的目标发现请求
+
+use anyhow::Result;
+
+use super::DebugClient;
+use crate::protocol::messages::{
+    AddonDescriptor, GetTabReply, ListAddonsReply, ListProcessesReply, ListServiceWorkerRegistrationsReply,
+    ListTabsReply, ListWorkersReply, ProcessDescriptor, RootForm, RootRequest, ServiceWorkerRegistrationDescriptor,
+    TabDescriptor, WorkerDescriptor,
+};
+
+impl DebugClient {
+    /// 列出所有tab
+    pub async fn list_tabs(&self) -> Result<Vec<TabDescriptor>> {
+        let reply: ListTabsReply = self.request_typed("root", &RootRequest::ListTabs).await?;
+        Ok(reply.tabs)
+    }
+
+    /// 按 `browser_id` 获取tab，`None` 表示当前选中的tab
+    pub async fn get_tab(&self, browser_id: Option<u64>) -> Result<TabDescriptor> {
+        let reply: GetTabReply = self.request_typed("root", &RootRequest::GetTab { browser_id }).await?;
+        Ok(reply.tab)
+    }
+
+    /// 列出所有内容进程
+    pub async fn list_processes(&self) -> Result<Vec<ProcessDescriptor>> {
+        let reply: ListProcessesReply = self.request_typed("root", &RootRequest::ListProcesses).await?;
+        Ok(reply.processes)
+    }
+
+    /// 列出所有worker
+    pub async fn list_workers(&self) -> Result<Vec<WorkerDescriptor>> {
+        let reply: ListWorkersReply = self.request_typed("root", &RootRequest::ListWorkers).await?;
+        Ok(reply.workers)
+    }
+
+    /// 列出所有service worker注册
+    pub async fn list_service_worker_registrations(&self) -> Result<Vec<ServiceWorkerRegistrationDescriptor>> {
+        let reply: ListServiceWorkerRegistrationsReply = self
+            .request_typed("root", &RootRequest::ListServiceWorkerRegistrations)
+            .await?;
+        Ok(reply.registrations)
+    }
+
+    /// 列出所有扩展
+    pub async fn list_addons(&self) -> Result<Vec<AddonDescriptor>> {
+        let reply: ListAddonsReply = self.request_typed("root", &RootRequest::ListAddons).await?;
+        Ok(reply.addons)
+    }
+
+    /// 获取root上的全局actors
+    pub async fn get_root(&self) -> Result<RootForm> {
+        self.request_typed("root", &RootRequest::GetRoot).await
+    }
+}
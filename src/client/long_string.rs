@@ -0,0 +1,90 @@
+//! 长字符串actor：服务器只发送字符串开头的一部分，其余内容通过 `substring` 按需获取
+//!
+//! 长度和位置都以UTF-16码元计，与JavaScript字符串一致。
+
+use anyhow::Result;
+use std::sync::Arc;
+
+use super::{DebugClient, Shared};
+use crate::protocol::grip::{Grip, LongStringGrip};
+use crate::protocol::messages::{LongStringRequest, SubstringReply};
+
+/// `fetch_full` 每次请求的长度
+pub const LONG_STRING_CHUNK: u64 = 64 * 1024;
+
+impl DebugClient {
+    /// 为长字符串grip构造客户端句柄
+    pub fn long_string(&self, grip: LongStringGrip) -> LongString {
+        LongString::new(&self.shared, grip)
+    }
+}
+
+/// 按 `ClientOptions::long_string_limit` 把长字符串grip替换为完整的字符串
+pub(super) async fn resolve(shared: &Arc<Shared>, grip: &mut Grip) -> Result<()> {
+    let Some(limit) = shared.long_string_limit else {
+        return Ok(());
+    };
+    let Some(long_string) = grip.as_long_string().filter(|long_string| long_string.length <= limit) else {
+        return Ok(());
+    };
+    let full = LongString::new(shared, long_string.clone()).fetch_full().await?;
+    *grip = Grip::String(full);
+    Ok(())
+}
+
+/// Long string actor句柄
+#[derive(Clone)]
+pub struct LongString {
+    grip: LongStringGrip,
+    shared: Arc<Shared>,
+}
+
+impl LongString {
+    pub(super) fn new(shared: &Arc<Shared>, grip: LongStringGrip) -> Self {
+        Self {
+            grip,
+            shared: shared.clone(),
+        }
+    }
+
+    pub fn actor(&self) -> &str {
+        &self.grip.actor
+    }
+
+    /// grip中已经带有的开头部分
+    pub fn initial(&self) -> &str {
+        &self.grip.initial
+    }
+
+    /// 完整长度
+    pub fn length(&self) -> u64 {
+        self.grip.length
+    }
+
+    /// 获取 `[start, end)` 范围内的内容
+    pub async fn substring(&self, start: u64, end: u64) -> Result<String> {
+        let reply: SubstringReply = self
+            .shared
+            .request(&self.grip.actor, serde_json::to_value(LongStringRequest::Substring { start, end })?)
+            .await?
+            .parse()?;
+        Ok(reply.substring)
+    }
+
+    /// 从 `initial` 之后开始分块获取完整内容
+    pub async fn fetch_full(&self) -> Result<String> {
+        let mut text = self.grip.initial.clone();
+        let mut start = text.encode_utf16().count() as u64;
+        while start < self.grip.length {
+            let end = (start + LONG_STRING_CHUNK).min(self.grip.length);
+            text.push_str(&self.substring(start, end).await?);
+            start = end;
+        }
+        Ok(text)
+    }
+
+    /// 释放服务器上的long string actor
+    pub async fn release(&self) -> Result<()> {
+        self.shared.release(&self.grip.actor).await
+    }
+}
@@ -1,123 +1,851 @@
-use std::net::TcpStream;
-use std::collections::HashMap;
+mod discovery;
+mod long_string;
+mod network;
+mod object;
+mod reconnect;
+mod sources;
+mod subscription;
+mod target;
+mod watcher;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
 use anyhow::Result;
-use tokio::sync::mpsc;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter, WriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc, oneshot, watch, Mutex, OwnedMappedMutexGuard};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
 use uuid::Uuid;
 
-use crate::protocol::{JsonPacketStream, Message};
+use crate::protocol::messages::{
+    ConsoleRequest, ServerInfo, SetBreakpointReply, TabDescriptor, TabEvent, ThreadEvent, ThreadRequest,
+};
+use crate::protocol::recording::{Direction, Recorder};
+use crate::protocol::thread::{Breakpoint, BreakpointLocation};
+use crate::protocol::{self, AsyncStream, BulkHeader, Message, Packet, PacketLimits, ProtocolError};
 use crate::actors::{
     Actor,
+    ActorRegistry,
     console::ConsoleActor,
     debugger::DebuggerActor,
     network::NetworkActor,
     root::RootActor,
-    tab::TabActor,
 };
 
+pub use long_string::{LongString, LONG_STRING_CHUNK};
+pub use network::NetworkEventClient;
+pub use object::{ObjectClient, PropertyIterator};
+pub use reconnect::{ConnectionEvent, Connector, ReconnectPolicy};
+pub use target::{wait_for_navigation, ConsoleClient, Navigation, Target, ThreadClient};
+pub use watcher::{ResourceChange, ResourceEvent, ResourceStream, WatcherClient};
+pub use sources::{SourceClient, SourceText};
+pub use subscription::{BulkStream, Subscription, DEFAULT_EVENT_BUFFER};
+use sources::SourceCache;
+use subscription::Subscribers;
+
+type PendingReply = oneshot::Sender<Result<Message, ProtocolError>>;
+
+/// 等待回复的请求；请求被取消后 `tx` 为 `None`，但在 `expires` 之前仍占据队列中的位置，
+/// 使迟到的回复被丢弃而不是被当作下一个请求的回复
+struct PendingEntry {
+    id: u64,
+    tx: Option<PendingReply>,
+    expires: Option<Instant>,
+}
+type Writer = BufWriter<WriteHalf<Box<dyn AsyncStream>>>;
+
+/// 未设置请求超时时，等待服务器hello数据包的最长时间
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 未设置超时的请求被放弃后，其占位等待迟到回复的最长时间
+const CANCELLED_REPLY_GRACE: Duration = Duration::from_secs(10);
+
+/// 当前连接的握手状态
+#[derive(Clone)]
+enum Handshake {
+    Pending,
+    Ready(ServerInfo),
+    Failed(String),
+}
+
+/// 客户端配置
+#[derive(Clone, Default)]
+pub struct ClientOptions {
+    /// 设置后会把收发的每个数据包写入录制文件
+    pub recorder: Option<Arc<Recorder>>,
+    /// 接收数据包时的大小限制
+    pub limits: PacketLimits,
+    /// 请求的默认超时时间，`None` 表示一直等待
+    pub request_timeout: Option<Duration>,
+    /// 设置后连接断开时自动重连并恢复会话，仅对 `connect*`/`from_connector` 创建的客户端有效
+    pub reconnect: Option<ReconnectPolicy>,
+    /// 设置后，控制台求值结果和网络请求/响应正文中长度不超过该值的长字符串会自动获取完整内容
+    pub long_string_limit: Option<u64>,
+}
+
+/// 通过 `select_tab` 选中的tab，重连后会按URL重新选择
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedTab {
+    pub url: String,
+    /// tab描述符actor
+    pub actor: String,
+    pub target_actor: String,
+    pub thread_actor: String,
+    pub console_actor: String,
+}
+
+/// 客户端与后台读取任务共享的状态
+struct Shared {
+    /// 当前连接的写半部，断线期间为 `None`
+    writer: Mutex<Option<Writer>>,
+    /// 每个actor按发送顺序排队的待回复请求
+    pending: StdMutex<HashMap<String, VecDeque<PendingEntry>>>,
+    next_request_id: AtomicU64,
+    request_timeout: Option<Duration>,
+    long_string_limit: Option<u64>,
+    subscribers: StdMutex<Subscribers>,
+    recorder: Option<Arc<Recorder>>,
+    registry: ActorRegistry,
+    selected_tab: StdMutex<Option<SelectedTab>>,
+    sources: StdMutex<SourceCache>,
+    /// 本地记录断点的debugger actor名称
+    debugger_actor: String,
+    /// 本地记录网络监听的network actor名称
+    network_actor: String,
+    connection_events: broadcast::Sender<ConnectionEvent>,
+    handshake: watch::Sender<Handshake>,
+}
+
+impl Shared {
+    /// 将读取到的消息交给对应的请求或事件订阅者
+    ///
+    /// RDP的回复不带请求id，每个actor按请求顺序依次回复，
+    /// 因此非事件类型的消息总是交给该actor最早的未完成请求。
+    /// 连接时的hello数据包不是回复，总是交给订阅者。
+    fn route(&self, msg: Message) {
+        let is_event = msg.is_hello() || msg.packet_type().is_some_and(protocol::is_unsolicited_event);
+        if !is_event && let Some(entry) = self.take_pending(&msg.from) {
+            let Some(tx) = entry.tx else {
+                log::debug!("Discarding reply from {} to a cancelled request", msg.from);
+                return;
+            };
+            let reply = match msg.content.get("error").and_then(Value::as_str) {
+                Some(error) => Err(ProtocolError::ActorError {
+                    actor: msg.from.clone(),
+                    error: error.to_string(),
+                    message: msg
+                        .content
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                }),
+                None => Ok(msg),
+            };
+            // 接收方已放弃等待时直接丢弃回复
+            let _ = tx.send(reply);
+            return;
+        }
+        match msg.packet_type() {
+            Some("tabNavigated") | Some("will-navigate") => self.track_navigation(&msg),
+            Some("newSource") => {
+                if let Ok(ThreadEvent::NewSource { source }) = msg.parse() {
+                    self.sources.lock().unwrap().insert(&msg.from, source);
+                }
+            }
+            _ => {}
+        }
+        self.subscribers.lock().unwrap().dispatch(&msg);
+    }
+
+    /// target导航时清除其线程在旧页面上的源码；选中的tab导航后更新其url，使重连后选择导航后的页面
+    fn track_navigation(&self, msg: &Message) {
+        let (url, state) = match msg.parse() {
+            Ok(TabEvent::WillNavigate { .. }) => {
+                self.sources.lock().unwrap().navigate(&msg.from, false);
+                return;
+            }
+            Ok(TabEvent::TabNavigated { url, state, .. }) => (url, state),
+            _ => return,
+        };
+        let finished = state.as_deref() != Some("start");
+        self.sources.lock().unwrap().navigate(&msg.from, finished);
+        if let Some(tab) = self.selected_tab.lock().unwrap().as_mut()
+            && tab.target_actor == msg.from
+            && finished
+        {
+            tab.url = url;
+        }
+    }
+
+    /// 取出actor最早的未完成请求，跳过已过期的取消占位
+    fn take_pending(&self, actor: &str) -> Option<PendingEntry> {
+        let mut pending = self.pending.lock().unwrap();
+        let queue = pending.get_mut(actor)?;
+        let now = Instant::now();
+        while queue.front().and_then(|entry| entry.expires).is_some_and(|expires| expires <= now) {
+            queue.pop_front();
+        }
+        let entry = queue.pop_front();
+        if queue.is_empty() {
+            pending.remove(actor);
+        }
+        entry
+    }
+
+    /// 取消请求：已发送的请求保留占位 `grace` 时间以丢弃迟到的回复，`grace` 为 `None` 表示未发送成功，直接移出队列
+    fn cancel_pending(&self, actor: &str, id: u64, grace: Option<Duration>) {
+        let mut pending = self.pending.lock().unwrap();
+        let Some(queue) = pending.get_mut(actor) else {
+            return;
+        };
+        let Some(pos) = queue.iter().position(|entry| entry.id == id) else {
+            return;
+        };
+        if let Some(grace) = grace {
+            queue[pos].tx = None;
+            queue[pos].expires = Some(Instant::now() + grace);
+        } else {
+            queue.remove(pos);
+            if queue.is_empty() {
+                pending.remove(actor);
+            }
+        }
+    }
+
+    /// 释放服务器上的actor，并丢弃它再也不会收到回复的取消占位
+    async fn release(&self, actor: &str) -> Result<()> {
+        self.request(actor, serde_json::json!({ "type": "release" })).await?;
+        let mut pending = self.pending.lock().unwrap();
+        if let Some(queue) = pending.get_mut(actor) {
+            queue.retain(|entry| entry.tx.is_some());
+            if queue.is_empty() {
+                pending.remove(actor);
+            }
+        }
+        Ok(())
+    }
+
+    fn record_json(&self, direction: Direction, packet: &Value) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record_json(direction, packet);
+        }
+    }
+
+    fn record_bulk(&self, direction: Direction, header: &BulkHeader, body: &[u8]) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record_bulk(direction, header, body);
+        }
+    }
+
+    async fn write_message(&self, writer: &mut Option<Writer>, msg: &Message) -> Result<()> {
+        let Some(writer) = writer.as_mut() else {
+            return Err(ProtocolError::Protocol("not connected".to_string()).into());
+        };
+        if self.recorder.is_some() {
+            self.record_json(Direction::Sent, &serde_json::to_value(msg)?);
+        }
+        protocol::write_json_packet(writer, msg).await
+    }
+
+    async fn send_message(&self, msg: &Message) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+        self.write_message(&mut writer, msg).await
+    }
+
+    async fn request(&self, actor: &str, packet: Value) -> Result<Message> {
+        self.request_with_timeout(actor, packet, self.request_timeout).await
+    }
+
+    async fn request_with_timeout(&self, actor: &str, packet: Value, timeout: Option<Duration>) -> Result<Message> {
+        let packet_type = packet.get("type").and_then(Value::as_str).unwrap_or_default().to_string();
+        let id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        let rx = {
+            // 持有写锁入队，保证排队顺序与发送顺序一致
+            let mut writer = self.writer.lock().await;
+            let (tx, rx) = oneshot::channel();
+            self.pending
+                .lock()
+                .unwrap()
+                .entry(actor.to_string())
+                .or_default()
+                .push_back(PendingEntry {
+                    id,
+                    tx: Some(tx),
+                    expires: None,
+                });
+            let msg = Message {
+                from: String::new(),
+                to: Some(actor.to_string()),
+                content: packet,
+            };
+            if let Err(e) = self.write_message(&mut writer, &msg).await {
+                self.cancel_pending(actor, id, None);
+                return Err(e);
+            }
+            rx
+        };
+
+        // 超时或调用方放弃等待（future被丢弃）时取消请求
+        let mut guard = CancelOnDrop {
+            shared: self,
+            actor,
+            id,
+            grace: timeout.unwrap_or(CANCELLED_REPLY_GRACE),
+            armed: true,
+        };
+        let reply = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, rx).await {
+                Ok(reply) => reply,
+                Err(_) => {
+                    return Err(ProtocolError::Timeout {
+                        actor: actor.to_string(),
+                        packet_type,
+                    }
+                    .into())
+                }
+            },
+            None => rx.await,
+        };
+        guard.armed = false;
+
+        match reply {
+            Ok(reply) => Ok(reply?),
+            Err(_) => Err(ProtocolError::Protocol(format!(
+                "connection closed while waiting for reply from {}",
+                actor
+            ))
+            .into()),
+        }
+    }
+
+    fn selected_tab(&self) -> Option<SelectedTab> {
+        self.selected_tab.lock().unwrap().clone()
+    }
+
+    /// 处理连接上的第一个数据包；`strict` 时第一个数据包必须是hello
+    fn handshake(&self, first: Option<&Message>, strict: bool) -> Result<(), ProtocolError> {
+        let state = match first {
+            Some(msg) if msg.is_hello() => match msg.parse::<ServerInfo>() {
+                Ok(info) => Handshake::Ready(info),
+                Err(e) => Handshake::Failed(format!("malformed hello packet: {}", e)),
+            },
+            _ if !strict => return Ok(()),
+            Some(msg) => Handshake::Failed(format!(
+                "expected a hello packet from root, got {}",
+                serde_json::to_string(msg).unwrap_or_default()
+            )),
+            None => Handshake::Failed("expected a hello packet from root".to_string()),
+        };
+        let result = match &state {
+            Handshake::Failed(reason) => Err(ProtocolError::NotRdpServer(reason.clone())),
+            _ => Ok(()),
+        };
+        self.handshake.send_replace(state);
+        result
+    }
+
+    /// 等待当前连接的hello数据包
+    async fn wait_for_hello(&self, timeout: Duration) -> Result<ServerInfo> {
+        let mut rx = self.handshake.subscribe();
+        let state = match tokio::time::timeout(timeout, rx.wait_for(|state| !matches!(state, Handshake::Pending))).await {
+            Ok(Ok(state)) => state.clone(),
+            _ => Handshake::Failed(format!("no hello packet within {:?}", timeout)),
+        };
+        match state {
+            Handshake::Ready(info) => Ok(info),
+            Handshake::Failed(reason) => Err(ProtocolError::NotRdpServer(reason).into()),
+            Handshake::Pending => unreachable!(),
+        }
+    }
+
+    fn server_info(&self) -> Option<ServerInfo> {
+        match &*self.handshake.borrow() {
+            Handshake::Ready(info) => Some(info.clone()),
+            _ => None,
+        }
+    }
+
+    fn emit(&self, event: ConnectionEvent) {
+        // 没有接收者时忽略
+        let _ = self.connection_events.send(event);
+    }
+
+    /// 连接断开：丢弃写半部，所有未完成请求失败，订阅保持不变以便重连后继续接收
+    async fn disconnect(&self) {
+        let mut writer = self.writer.lock().await;
+        *writer = None;
+        self.pending.lock().unwrap().clear();
+        // source actors只在原连接上有效
+        self.sources.lock().unwrap().clear();
+        self.handshake.send_replace(Handshake::Pending);
+    }
+
+    /// 连接关闭：所有未完成请求失败，所有订阅结束
+    fn close(&self) {
+        self.pending.lock().unwrap().clear();
+        self.subscribers.lock().unwrap().close();
+    }
+}
+
+/// 请求完成前被丢弃时取消对应的等待项
+struct CancelOnDrop<'a> {
+    shared: &'a Shared,
+    actor: &'a str,
+    id: u64,
+    grace: Duration,
+    armed: bool,
+}
+
+impl Drop for CancelOnDrop<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.shared.cancel_pending(self.actor, self.id, Some(self.grace));
+        }
+    }
+}
+
 pub struct DebugClient {
-    stream: TcpStream,
-    actors: HashMap<String, Box<dyn Actor + Send>>,
-    message_tx: mpsc::Sender<Message>,
+    shared: Arc<Shared>,
+    reader: JoinHandle<()>,
+    /// 未被请求或其他订阅消费的消息，由 `receive_message` 读取
+    events: Subscription,
+    actors: ActorRegistry,
+    /// 远程actor名称到本客户端为其注册的本地actor名称
+    mirrors: StdMutex<HashMap<String, String>>,
 }
 
 impl DebugClient {
     /// 创建新的调试客户端连接
     pub async fn connect(host: &str, port: u16) -> Result<Self> {
-        let stream = TcpStream::connect((host, port))?;
-        stream.set_nonblocking(true)?;
-        
-        let (tx, mut rx) = mpsc::channel(100);
-        
-        let mut client = Self {
-            stream,
-            actors: HashMap::new(),
-            message_tx: tx,
-        };
-
-        // 初始化基本actors
-        client.init_actors();
-
-        // 启动消息处理循环
-        let actors = client.actors.clone();
-        tokio::spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                if let Some(actor) = msg.to.as_ref().and_then(|to| actors.get(to)) {
-                    if let Ok(Some(response)) = actor.handle_message(msg).await {
-                        // TODO: 发送响应
-                        log::debug!("Actor response: {:?}", response);
-                    }
-                }
-            }
+        Self::connect_with_options(host, port, ClientOptions::default()).await
+    }
+
+    /// 使用指定配置创建调试客户端连接
+    pub async fn connect_with_options(host: &str, port: u16, options: ClientOptions) -> Result<Self> {
+        let host = host.to_string();
+        let connector: Connector = Arc::new(move || {
+            let host = host.clone();
+            Box::pin(async move {
+                let stream = TcpStream::connect((host.as_str(), port)).await?;
+                stream.set_nodelay(true)?;
+                Ok(Box::new(stream) as Box<dyn AsyncStream>)
+            })
         });
+        Self::from_connector(connector, options).await
+    }
 
+    /// 使用自定义的连接函数创建调试客户端；开启重连时断线后会再次调用该函数
+    ///
+    /// 连接后等待服务器的hello数据包，对端不是RDP服务器时返回 `ProtocolError::NotRdpServer`。
+    pub async fn from_connector(connector: Connector, options: ClientOptions) -> Result<Self> {
+        let stream = connector().await?;
+        let timeout = options.request_timeout.unwrap_or(HELLO_TIMEOUT);
+        let client = Self::start(stream, options, Some(connector));
+        client.shared.wait_for_hello(timeout).await?;
         Ok(client)
     }
 
-    /// 初始化基本actors
-    fn init_actors(&mut self) {
+    /// 基于任意异步字节流创建调试客户端（TcpStream、UnixStream、内存duplex等）
+    ///
+    /// 读半部由后台任务持有，负责把回复交给等待中的请求并把事件分发给订阅者。
+    pub fn from_stream<S: AsyncStream + 'static>(stream: S) -> Result<Self> {
+        Self::from_stream_with_options(stream, ClientOptions::default())
+    }
+
+    /// 基于任意异步字节流，使用指定配置创建调试客户端；无法重新建立连接，因此不会重连
+    ///
+    /// 不等待hello数据包，需要服务器信息时可调用 `wait_for_hello`。
+    pub fn from_stream_with_options<S: AsyncStream + 'static>(stream: S, options: ClientOptions) -> Result<Self> {
+        Ok(Self::start(Box::new(stream), options, None))
+    }
+
+    fn start(stream: Box<dyn AsyncStream>, options: ClientOptions, connector: Option<Connector>) -> Self {
+        let (reader, writer) = tokio::io::split(stream);
+        let actors = ActorRegistry::new();
+        let (debugger_actor, network_actor) = Self::init_actors(&actors);
+        let shared = Arc::new(Shared {
+            writer: Mutex::new(Some(BufWriter::new(writer))),
+            pending: StdMutex::default(),
+            next_request_id: AtomicU64::new(0),
+            request_timeout: options.request_timeout,
+            long_string_limit: options.long_string_limit,
+            subscribers: StdMutex::default(),
+            recorder: options.recorder,
+            registry: actors.clone(),
+            selected_tab: StdMutex::default(),
+            sources: StdMutex::default(),
+            debugger_actor,
+            network_actor,
+            connection_events: broadcast::channel(16).0,
+            handshake: watch::Sender::new(Handshake::Pending),
+        });
+        let events = shared
+            .subscribers
+            .lock()
+            .unwrap()
+            .subscribe_fallback(DEFAULT_EVENT_BUFFER);
+        // 只有通过连接函数建立的连接才要求第一个数据包是hello
+        let strict = connector.is_some();
+        let reconnect = connector.zip(options.reconnect);
+        let reader = tokio::spawn(reconnect::supervise(
+            shared.clone(),
+            BufReader::new(reader),
+            options.limits,
+            strict,
+            reconnect,
+        ));
+
+        Self {
+            shared,
+            reader,
+            events,
+            actors,
+            mirrors: StdMutex::default(),
+        }
+    }
+
+    /// 初始化基本actors，返回debugger和network actor的名称
+    fn init_actors(actors: &ActorRegistry) -> (String, String) {
         // 添加root actor
-        let root_actor = RootActor::new();
-        self.actors.insert("root".to_string(), Box::new(root_actor));
+        actors.register(RootActor::new());
 
         // 添加console actor
-        let console_actor = ConsoleActor::new(format!("console-{}", Uuid::new_v4()));
-        self.actors.insert(console_actor.name().to_string(), Box::new(console_actor));
+        actors.register(ConsoleActor::new(format!("console-{}", Uuid::new_v4())));
 
         // 添加debugger actor
-        let debugger_actor = DebuggerActor::new(format!("debugger-{}", Uuid::new_v4()));
-        self.actors.insert(debugger_actor.name().to_string(), Box::new(debugger_actor));
+        let debugger = actors.register(DebuggerActor::new(format!("debugger-{}", Uuid::new_v4())));
 
         // 添加network actor
-        let network_actor = NetworkActor::new(format!("network-{}", Uuid::new_v4()));
-        self.actors.insert(network_actor.name().to_string(), Box::new(network_actor));
+        let network = actors.register(NetworkActor::new(format!("network-{}", Uuid::new_v4())));
+
+        (debugger, network)
     }
 
     /// 发送消息到服务器
-    pub async fn send_message(&mut self, msg: Message) -> Result<()> {
-        self.stream.write_json_packet(&msg)?;
+    pub async fn send_message(&self, msg: Message) -> Result<()> {
+        self.shared.send_message(&msg).await
+    }
+
+    /// 向actor发送请求并等待其回复
+    ///
+    /// `{"error": ..., "message": ...}` 形式的回复会转换为 `ProtocolError::ActorError`；
+    /// 超过 `ClientOptions::request_timeout` 时返回 `ProtocolError::Timeout`。
+    /// 超时或丢弃返回的future都会取消请求，之后到达的回复会被丢弃。
+    pub async fn request(&self, actor: &str, packet: Value) -> Result<Message> {
+        self.shared.request(actor, packet).await
+    }
+
+    /// 使用指定的超时时间发送请求，覆盖客户端的默认超时
+    pub async fn request_with_timeout(&self, actor: &str, packet: Value, timeout: Duration) -> Result<Message> {
+        self.shared.request_with_timeout(actor, packet, Some(timeout)).await
+    }
+
+    /// 发送强类型请求，并把回复解析为 `R`
+    pub async fn request_typed<T, R>(&self, actor: &str, request: &T) -> Result<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        self.request(actor, serde_json::to_value(request)?).await?.parse()
+    }
+
+    /// 订阅某个actor的某类事件，例如 `client.subscribe("server1.conn0.thread1", "paused")`
+    pub fn subscribe(&self, actor: &str, event_type: &str) -> Subscription {
+        self.subscribe_with_capacity(Some(actor), Some(event_type), DEFAULT_EVENT_BUFFER)
+    }
+
+    /// 订阅事件并指定缓冲区大小，`None` 表示不按该字段过滤
+    pub fn subscribe_with_capacity(
+        &self,
+        actor: Option<&str>,
+        event_type: Option<&str>,
+        capacity: usize,
+    ) -> Subscription {
+        self.shared.subscribers.lock().unwrap().subscribe(
+            actor.map(str::to_string),
+            event_type.map(str::to_string),
+            capacity,
+        )
+    }
+
+    /// 订阅bulk数据包，`None` 表示不按该字段过滤；没有订阅者的bulk负载会被丢弃
+    pub fn subscribe_bulk(
+        &self,
+        actor: Option<&str>,
+        packet_type: Option<&str>,
+    ) -> mpsc::Receiver<BulkStream> {
+        self.shared.subscribers.lock().unwrap().subscribe_bulk(
+            actor.map(str::to_string),
+            packet_type.map(str::to_string),
+            DEFAULT_EVENT_BUFFER,
+        )
+    }
+
+    /// 等待服务器的hello数据包并返回服务器信息；已收到时立即返回
+    pub async fn wait_for_hello(&self, timeout: Duration) -> Result<ServerInfo> {
+        self.shared.wait_for_hello(timeout).await
+    }
+
+    /// 当前连接的服务器信息，尚未收到hello时为 `None`
+    pub fn server_info(&self) -> Option<ServerInfo> {
+        self.shared.server_info()
+    }
+
+    /// 服务器是否声明了某个特性，例如 `client.server_has_trait("watcher")`
+    pub fn server_has_trait(&self, name: &str) -> bool {
+        self.server_info().is_some_and(|info| info.has_trait(name))
+    }
+
+    /// 订阅连接状态变化（断开、重连成功或失败）
+    pub fn connection_events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.shared.connection_events.subscribe()
+    }
+
+    /// 发送bulk数据包，负载从 `source` 中流式读取；录制时负载会先读入内存
+    pub async fn send_bulk<R>(&self, header: &BulkHeader, source: &mut R) -> Result<()>
+    where
+        R: AsyncRead + Unpin + Send + ?Sized,
+    {
+        let mut writer = self.shared.writer.lock().await;
+        let Some(writer) = writer.as_mut() else {
+            return Err(ProtocolError::Protocol("not connected".to_string()).into());
+        };
+        if self.shared.recorder.is_none() {
+            return protocol::write_bulk_packet(writer, header, source).await;
+        }
+        let mut body = Vec::new();
+        source.take(header.length).read_to_end(&mut body).await?;
+        protocol::write_bulk_packet(writer, header, &mut body.as_slice()).await?;
+        self.shared.record_bulk(Direction::Sent, header, &body);
         Ok(())
     }
 
-    /// 接收服务器消息
+    /// 接收未被请求或其他订阅消费的服务器消息，连接关闭后返回 `None`
     pub async fn receive_message(&mut self) -> Result<Option<Message>> {
-        match self.stream.read_json_packet()? {
-            Some(value) => {
-                let msg: Message = serde_json::from_value(value)?;
-                
+        match self.events.recv().await {
+            Some(msg) => {
                 // 如果消息有目标actor，转发给对应的actor处理
-                if let Some(to) = msg.to.as_ref() {
-                    if let Some(actor) = self.actors.get_mut(to) {
-                        if let Ok(Some(response)) = actor.handle_message(msg.clone()).await {
-                            self.send_message(response).await?;
-                        }
-                    }
+                if let Ok(Some(response)) = self.actors.handle_message(msg.clone()).await {
+                    self.send_message(response).await?;
                 }
-                
+
                 Ok(Some(msg))
             }
             None => Ok(None),
         }
     }
 
-    /// 创建新的tab
-    pub async fn create_tab(&mut self, url: String) -> Result<String> {
-        let tab_id = format!("tab-{}", Uuid::new_v4());
-        let tab_actor = TabActor::new(
-            tab_id.clone(),
-            "New Tab".to_string(),
-            url,
-        );
-        self.actors.insert(tab_id.clone(), Box::new(tab_actor));
-        Ok(tab_id)
+    /// 按URL选择远端tab：获取其target、attach线程并返回console和thread句柄；
+    /// 重连后会自动重新选择同一URL的tab
+    pub async fn select_tab(&self, url: &str) -> Result<Target> {
+        target::select_tab(&self.shared, url).await
     }
 
-    /// 获取actor
-    pub fn get_actor(&self, name: &str) -> Option<&(dyn Actor + Send)> {
-        self.actors.get(name).map(|actor| actor.as_ref())
+    /// 对tab描述符调用 `getWatcher`，返回watcher句柄，用于监听targets和资源
+    pub async fn get_watcher(&self, descriptor: &TabDescriptor) -> Result<WatcherClient> {
+        watcher::get_watcher(&self.shared, descriptor).await
     }
 
-    /// 获取actor（可变）
-    pub fn get_actor_mut(&mut self, name: &str) -> Option<&mut (dyn Actor + Send)> {
-        self.actors.get_mut(name).map(|actor| actor.as_mut())
+    /// 对tab描述符调用 `getTarget`，返回target及其子actor句柄
+    pub async fn get_target(&self, descriptor: &TabDescriptor) -> Result<Target> {
+        target::get_target(&self.shared, descriptor).await
     }
-} 
\ No newline at end of file
+
+    /// 当前选中的tab
+    pub fn selected_tab(&self) -> Option<SelectedTab> {
+        self.shared.selected_tab()
+    }
+
+    /// 在选中tab的线程上设置断点，并记录到本地debugger actor以便重连后恢复
+    ///
+    /// 回复中带有断点actor和实际生效的位置；服务器会把断点滑动到最近的可断点位置。
+    pub async fn set_breakpoint(&self, breakpoint: Breakpoint) -> Result<SetBreakpointReply> {
+        let thread = self.selected_thread()?;
+        let request = ThreadRequest::SetBreakpoint {
+            location: breakpoint.location.clone(),
+            options: breakpoint.options.clone(),
+        };
+        let reply: SetBreakpointReply = self.request_typed(&thread, &request).await?;
+        if let Some(mut debugger) = self.actor::<DebuggerActor>(&self.shared.debugger_actor).await {
+            debugger.add_breakpoint(breakpoint);
+        }
+        Ok(reply)
+    }
+
+    /// 移除断点，同时从本地debugger actor中删除
+    pub async fn remove_breakpoint(&self, location: &BreakpointLocation) -> Result<()> {
+        let thread = self.selected_thread()?;
+        let request = ThreadRequest::RemoveBreakpoint {
+            location: location.clone(),
+        };
+        self.request(&thread, serde_json::to_value(request)?).await?;
+        if let Some(mut debugger) = self.actor::<DebuggerActor>(&self.shared.debugger_actor).await {
+            debugger.remove_breakpoint(location);
+        }
+        Ok(())
+    }
+
+    /// 在选中tab的console actor上开启网络监听，并记录到本地network actor以便重连后恢复
+    pub async fn start_network_listeners(&self) -> Result<Message> {
+        let Some(console) = self.selected_tab().map(|tab| tab.console_actor) else {
+            return Err(ProtocolError::Protocol("no tab selected".to_string()).into());
+        };
+        let listener = "NetworkActivity".to_string();
+        let request = ConsoleRequest::StartListeners {
+            listeners: vec![listener.clone()],
+        };
+        let reply = self.request(&console, serde_json::to_value(request)?).await?;
+        if let Some(mut network) = self.actor::<NetworkActor>(&self.shared.network_actor).await {
+            network.add_listener(listener);
+        }
+        Ok(reply)
+    }
+
+    /// 本地debugger actor的名称
+    pub fn debugger_actor(&self) -> &str {
+        &self.shared.debugger_actor
+    }
+
+    /// 本地network actor的名称
+    pub fn network_actor(&self) -> &str {
+        &self.shared.network_actor
+    }
+
+    fn selected_thread(&self) -> Result<String> {
+        match self.selected_tab() {
+            Some(tab) => Ok(tab.thread_actor),
+            None => Err(ProtocolError::Protocol("no tab selected".to_string()).into()),
+        }
+    }
+
+    /// 获取actor注册表
+    pub fn registry(&self) -> &ActorRegistry {
+        &self.actors
+    }
+
+    /// 获取指定类型的actor，例如 `client.actor::<NetworkActor>(name)`
+    pub async fn actor<A: Actor>(&self, name: &str) -> Option<OwnedMappedMutexGuard<dyn Actor, A>> {
+        self.actors.get::<A>(name).await
+    }
+
+    /// 在本地注册表中注册跟踪远程actor `remote` 的actor，返回本地名称；`release(remote)` 时一并移除
+    pub fn register_mirror<A: Actor>(&self, remote: &str, actor: A) -> String {
+        let local = self.actors.register(actor);
+        self.mirrors.lock().unwrap().insert(remote.to_string(), local.clone());
+        local
+    }
+
+    /// 释放服务器上的actor
+    ///
+    /// 本地注册表与服务器的actor名称相互独立，只移除通过 `register_mirror` 为该远程actor注册的本地actor。
+    pub async fn release(&self, actor: &str) -> Result<()> {
+        self.shared.release(actor).await?;
+        let local = self.mirrors.lock().unwrap().remove(actor);
+        if let Some(local) = local {
+            self.actors.remove(&local);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DebugClient {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// 读取当前连接上的数据包并分发，直到连接关闭
+///
+/// 第一个数据包用于握手，`strict` 时不是hello则断开连接。
+async fn read_loop(reader: &mut reconnect::Reader, shared: &Shared, limits: &PacketLimits, strict: bool) {
+    let mut first = true;
+    loop {
+        let packet = match protocol::read_packet_with_limits(reader, limits).await {
+            Ok(Some(packet)) => packet,
+            Ok(None) => break,
+            Err(e) => {
+                log::error!("Failed to read packet: {}", e);
+                break;
+            }
+        };
+        let msg = match &packet {
+            Packet::Json(value) => {
+                shared.record_json(Direction::Received, value);
+                match serde_json::from_value::<Message>(value.clone()) {
+                    Ok(msg) => Some(msg),
+                    Err(e) => {
+                        log::warn!("Ignoring malformed packet: {}", e);
+                        None
+                    }
+                }
+            }
+            Packet::Bulk(_) => None,
+        };
+        if std::mem::take(&mut first)
+            && let Err(e) = shared.handshake(msg.as_ref(), strict)
+        {
+            log::error!("Handshake failed: {}", e);
+            break;
+        }
+        match (packet, msg) {
+            (Packet::Json(_), Some(msg)) => shared.route(msg),
+            (Packet::Json(_), None) => {}
+            (Packet::Bulk(header), _) => {
+                let sink = shared.subscribers.lock().unwrap().dispatch_bulk(&header);
+                let mut recorded = shared.recorder.is_some().then(Vec::new);
+                if let Err(e) = forward_bulk(reader, &header, sink, recorded.as_mut()).await {
+                    log::error!("Failed to read bulk packet: {}", e);
+                    break;
+                }
+                if let Some(body) = recorded {
+                    shared.record_bulk(Direction::Received, &header, &body);
+                }
+            }
+        }
+    }
+    if first {
+        let _ = shared.handshake(None, strict);
+    }
+}
+
+/// 将bulk负载转发给订阅者，录制时同时保存到 `recorded`；订阅者中途放弃时继续读完负载以保持流同步
+async fn forward_bulk<R>(
+    reader: &mut R,
+    header: &BulkHeader,
+    mut sink: Option<tokio::io::DuplexStream>,
+    mut recorded: Option<&mut Vec<u8>>,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut payload = reader.take(header.length);
+    let mut buf = vec![0; 8192];
+    let mut received = 0;
+    loop {
+        let n = payload.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        received += n as u64;
+        if let Some(recorded) = recorded.as_mut() {
+            recorded.extend_from_slice(&buf[..n]);
+        }
+        if let Some(writer) = sink.as_mut()
+            && writer.write_all(&buf[..n]).await.is_err()
+        {
+            sink = None;
+        }
+    }
+    if received != header.length {
+        return Err(ProtocolError::UnexpectedEof.into());
+    }
+    if let Some(mut writer) = sink {
+        let _ = writer.shutdown().await;
+    }
+    Ok(())
+}
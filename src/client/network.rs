@@ -0,0 +1,59 @@
+//! Network event actor客户端：获取单个网络请求的详细信息
+
+use anyhow::Result;
+use serde_json::Value;
+use std::sync::Arc;
+
+use super::{long_string, DebugClient, Shared};
+use crate::protocol::messages::{
+    NetworkEventRequest, PostData, RequestPostDataReply, ResponseContent, ResponseContentReply,
+};
+use crate::protocol::Message;
+
+impl DebugClient {
+    /// 为 `networkEvent` 事件中的network event actor构造客户端句柄
+    pub fn network_event(&self, actor: &str) -> NetworkEventClient {
+        NetworkEventClient {
+            actor: actor.to_string(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+/// Network event actor句柄
+#[derive(Clone)]
+pub struct NetworkEventClient {
+    actor: String,
+    shared: Arc<Shared>,
+}
+
+impl NetworkEventClient {
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    /// 向network event actor发送任意请求
+    pub async fn request(&self, packet: Value) -> Result<Message> {
+        self.shared.request(&self.actor, packet).await
+    }
+
+    /// 获取响应正文；`content_discarded` 为 `true` 时正文为空
+    pub async fn get_response_content(&self) -> Result<ResponseContent> {
+        let mut reply: ResponseContentReply = self
+            .request(serde_json::to_value(NetworkEventRequest::GetResponseContent)?)
+            .await?
+            .parse()?;
+        long_string::resolve(&self.shared, &mut reply.content.text).await?;
+        Ok(reply.content)
+    }
+
+    /// 获取请求正文
+    pub async fn get_request_post_data(&self) -> Result<PostData> {
+        let mut reply: RequestPostDataReply = self
+            .request(serde_json::to_value(NetworkEventRequest::GetRequestPostData)?)
+            .await?
+            .parse()?;
+        long_string::resolve(&self.shared, &mut reply.post_data.text).await?;
+        Ok(reply.post_data)
+    }
+}
@@ -0,0 +1,121 @@
+//! Object actor客户端：按grip逐层查看对象的属性和原型
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use super::{DebugClient, Shared};
+use crate::protocol::grip::{Grip, ObjectGrip, PropertyDescriptor};
+use crate::protocol::messages::{
+    EnumPropertiesOptions, EnumPropertiesReply, ObjectRequest, OwnPropertyNamesReply, PropertyIteratorRequest,
+    PropertyIteratorSlice, PrototypeAndPropertiesReply, PrototypeReply,
+};
+
+impl DebugClient {
+    /// 为对象grip构造客户端句柄
+    pub fn object(&self, grip: ObjectGrip) -> ObjectClient {
+        ObjectClient {
+            grip,
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+/// Object actor句柄
+#[derive(Clone)]
+pub struct ObjectClient {
+    grip: ObjectGrip,
+    shared: Arc<Shared>,
+}
+
+impl ObjectClient {
+    pub fn actor(&self) -> &str {
+        &self.grip.actor
+    }
+
+    pub fn grip(&self) -> &ObjectGrip {
+        &self.grip
+    }
+
+    /// 为属性值等其他对象grip构造句柄；原始值返回 `None`
+    pub fn child(&self, grip: &Grip) -> Option<ObjectClient> {
+        grip.as_object().map(|object| ObjectClient {
+            grip: object.clone(),
+            shared: self.shared.clone(),
+        })
+    }
+
+    async fn request<T: serde::de::DeserializeOwned>(&self, request: ObjectRequest) -> Result<T> {
+        self.shared
+            .request(&self.grip.actor, serde_json::to_value(request)?)
+            .await?
+            .parse()
+    }
+
+    /// 获取原型和全部自有属性
+    pub async fn prototype_and_properties(&self) -> Result<PrototypeAndPropertiesReply> {
+        self.request(ObjectRequest::PrototypeAndProperties).await
+    }
+
+    pub async fn own_property_names(&self) -> Result<Vec<String>> {
+        let reply: OwnPropertyNamesReply = self.request(ObjectRequest::OwnPropertyNames).await?;
+        Ok(reply.own_property_names)
+    }
+
+    pub async fn get_prototype(&self) -> Result<Grip> {
+        let reply: PrototypeReply = self.request(ObjectRequest::GetPrototype).await?;
+        Ok(reply.prototype)
+    }
+
+    /// 创建属性迭代器，适合分批读取属性很多的对象
+    pub async fn enum_properties(&self, options: EnumPropertiesOptions) -> Result<PropertyIterator> {
+        let reply: EnumPropertiesReply = self.request(ObjectRequest::EnumProperties { options }).await?;
+        Ok(PropertyIterator {
+            actor: reply.iterator.actor,
+            count: reply.iterator.count,
+            shared: self.shared.clone(),
+        })
+    }
+
+    /// 释放服务器上的object actor，之后该句柄不再可用
+    pub async fn release(&self) -> Result<()> {
+        self.shared.release(&self.grip.actor).await
+    }
+}
+
+/// Property iterator actor句柄
+#[derive(Clone)]
+pub struct PropertyIterator {
+    actor: String,
+    count: u64,
+    shared: Arc<Shared>,
+}
+
+impl PropertyIterator {
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    /// 属性总数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    async fn request(&self, request: PropertyIteratorRequest) -> Result<BTreeMap<String, PropertyDescriptor>> {
+        let reply: PropertyIteratorSlice = self
+            .shared
+            .request(&self.actor, serde_json::to_value(request)?)
+            .await?
+            .parse()?;
+        Ok(reply.own_properties)
+    }
+
+    /// 读取从 `start` 开始的 `count` 个属性
+    pub async fn slice(&self, start: u64, count: u64) -> Result<BTreeMap<String, PropertyDescriptor>> {
+        self.request(PropertyIteratorRequest::Slice { start, count }).await
+    }
+
+    pub async fn all(&self) -> Result<BTreeMap<String, PropertyDescriptor>> {
+        self.request(PropertyIteratorRequest::All).await
+    }
+}
@@ -0,0 +1,146 @@
+//! 断线重连与会话恢复
+//!
+//! 开启重连后，连接断开时后台任务按指数退避重新建立连接，
+//! 然后重新选择之前的tab、恢复断点和网络监听，并通过 `ConnectionEvent` 通知调用方。
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{BufReader, BufWriter, ReadHalf};
+
+use super::Shared;
+use crate::actors::{debugger::DebuggerActor, network::NetworkActor};
+use crate::protocol::{AsyncStream, PacketLimits};
+
+/// 建立新连接的函数，每次重连时调用
+pub type Connector = Arc<dyn Fn() -> BoxFuture<'static, Result<Box<dyn AsyncStream>>> + Send + Sync>;
+
+/// 重连策略：第n次尝试前等待 `initial_delay * 2^(n-1)`，最长 `max_delay`
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// 放弃前的最大尝试次数
+    pub max_attempts: u32,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            max_attempts: 10,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// 第 `attempt` 次（从1开始）尝试前的等待时间
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// 连接状态变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// 连接断开，未完成的请求均已失败
+    Disconnected,
+    /// 已重新连接并恢复会话
+    Reconnected { attempts: u32 },
+    /// 已重新连接，但恢复会话失败
+    RestoreFailed { error: String },
+    /// 达到最大尝试次数，客户端不再重连
+    ReconnectFailed,
+}
+
+pub(crate) type Reader = BufReader<ReadHalf<Box<dyn AsyncStream>>>;
+
+/// 后台连接任务：读取数据包直到断开，按策略重连，最终关闭所有请求和订阅
+pub(crate) async fn supervise(
+    shared: Arc<Shared>,
+    mut reader: Reader,
+    limits: PacketLimits,
+    strict: bool,
+    reconnect: Option<(Connector, ReconnectPolicy)>,
+) {
+    loop {
+        super::read_loop(&mut reader, &shared, &limits, strict).await;
+        let Some((connector, policy)) = &reconnect else {
+            break;
+        };
+        shared.disconnect().await;
+        shared.emit(ConnectionEvent::Disconnected);
+
+        let Some((stream, attempts)) = connect_with_backoff(connector, policy).await else {
+            log::error!("Giving up after {} reconnect attempts", policy.max_attempts);
+            shared.emit(ConnectionEvent::ReconnectFailed);
+            break;
+        };
+        let (new_reader, writer) = tokio::io::split(stream);
+        *shared.writer.lock().await = Some(BufWriter::new(writer));
+        reader = BufReader::new(new_reader);
+
+        // 恢复请求的回复由本任务读取，因此恢复过程必须在独立任务中运行
+        let shared = shared.clone();
+        tokio::spawn(async move {
+            let restored = async {
+                shared.wait_for_hello(super::HELLO_TIMEOUT).await?;
+                restore_session(&shared).await
+            };
+            let event = match restored.await {
+                Ok(()) => ConnectionEvent::Reconnected { attempts },
+                Err(e) => {
+                    log::error!("Failed to restore session: {}", e);
+                    ConnectionEvent::RestoreFailed { error: e.to_string() }
+                }
+            };
+            shared.emit(event);
+        });
+    }
+    shared.close();
+}
+
+async fn connect_with_backoff(connector: &Connector, policy: &ReconnectPolicy) -> Option<(Box<dyn AsyncStream>, u32)> {
+    for attempt in 1..=policy.max_attempts {
+        tokio::time::sleep(policy.delay(attempt)).await;
+        match connector().await {
+            Ok(stream) => {
+                log::info!("Reconnected after {} attempt(s)", attempt);
+                return Some((stream, attempt));
+            }
+            Err(e) => log::warn!("Reconnect attempt {} failed: {}", attempt, e),
+        }
+    }
+    None
+}
+
+/// 重新选择之前的tab，并重新设置本地记录的断点和网络监听
+async fn restore_session(shared: &Arc<Shared>) -> Result<()> {
+    let Some(url) = shared.selected_tab().map(|tab| tab.url) else {
+        return Ok(());
+    };
+    let target = super::target::select_tab(shared, &url).await?;
+
+    let breakpoints: Vec<_> = match shared.registry.get::<DebuggerActor>(&shared.debugger_actor).await {
+        Some(debugger) => debugger.breakpoints().cloned().collect(),
+        None => Vec::new(),
+    };
+    for breakpoint in breakpoints {
+        target.thread.set_breakpoint(&breakpoint).await?;
+    }
+
+    let listeners: Vec<_> = match shared.registry.get::<NetworkActor>(&shared.network_actor).await {
+        Some(network) => network.listeners().to_vec(),
+        None => Vec::new(),
+    };
+    if !listeners.is_empty() {
+        shared
+            .request(target.console.actor(), serde_json::json!({"type": "startListeners", "listeners": listeners}))
+            .await?;
+    }
+    Ok(())
+}
+
@@ -0,0 +1,217 @@
+//! 源码列表、源码文本和本地源码缓存
+//!
+//! 线程的 `sources` 回复和 `newSource` 事件中的源码描述按actor和URL缓存，
+//! 获取过的源码文本同样缓存，便于在暂停位置附近显示代码。缓存在断线时清空，
+//! target导航后清除其线程在旧页面上的源码。
+
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use super::long_string::LongString;
+use super::{DebugClient, Shared};
+use crate::protocol::thread::Location;
+use crate::protocol::grip::Grip;
+use crate::protocol::messages::{
+    BreakableLinesReply, BreakpointPositionsReply, PositionQuery, SourceForm, SourceReply, SourceRequest,
+};
+use crate::protocol::ProtocolError;
+
+/// 源码文本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceText {
+    pub text: String,
+    /// 例如 `text/javascript`
+    pub content_type: Option<String>,
+}
+
+/// 按actor和URL索引的源码缓存
+#[derive(Default)]
+pub(super) struct SourceCache {
+    forms: HashMap<String, SourceForm>,
+    /// URL到actor的映射，同一URL以最新的源码为准
+    urls: HashMap<String, String>,
+    texts: HashMap<String, SourceText>,
+    /// source actor所属的thread actor
+    threads: HashMap<String, String>,
+    /// target actor到其thread actor的映射，用于在导航时找到要清除的源码
+    targets: HashMap<String, String>,
+    /// 已开始导航、尚未加载完成的线程
+    navigating: HashSet<String>,
+}
+
+impl SourceCache {
+    /// 缓存 `thread` 上加载的源码
+    pub(super) fn insert(&mut self, thread: &str, form: SourceForm) {
+        if let Some(url) = &form.url {
+            self.urls.insert(url.clone(), form.actor.clone());
+        }
+        self.threads.insert(form.actor.clone(), thread.to_string());
+        self.forms.insert(form.actor.clone(), form);
+    }
+
+    /// 记录target的thread actor
+    pub(super) fn track_target(&mut self, target: &str, thread: &str) {
+        self.targets.insert(target.to_string(), thread.to_string());
+    }
+
+    /// target开始或完成导航：开始时清除线程在旧页面上的源码，加载期间新到达的源码保留；
+    /// 没有收到开始事件就完成导航时在完成时清除
+    pub(super) fn navigate(&mut self, target: &str, finished: bool) {
+        let Some(thread) = self.targets.get(target).cloned() else {
+            return;
+        };
+        let clear = if finished {
+            !self.navigating.remove(&thread)
+        } else {
+            self.navigating.insert(thread.clone())
+        };
+        if clear {
+            self.clear_thread(&thread);
+        }
+    }
+
+    /// 清除某个线程的所有源码
+    fn clear_thread(&mut self, thread: &str) {
+        let actors: Vec<_> = self
+            .threads
+            .iter()
+            .filter(|(_, t)| *t == thread)
+            .map(|(actor, _)| actor.clone())
+            .collect();
+        for actor in actors {
+            self.threads.remove(&actor);
+            self.texts.remove(&actor);
+            if let Some(url) = self.forms.remove(&actor).and_then(|form| form.url)
+                && self.urls.get(&url) == Some(&actor)
+            {
+                self.urls.remove(&url);
+            }
+        }
+    }
+
+    /// 按actor名称或URL查找
+    fn find(&self, id: &str) -> Option<&SourceForm> {
+        self.forms
+            .get(id)
+            .or_else(|| self.urls.get(id).and_then(|actor| self.forms.get(actor)))
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.forms.clear();
+        self.urls.clear();
+        self.texts.clear();
+        self.threads.clear();
+        self.targets.clear();
+        self.navigating.clear();
+    }
+}
+
+impl DebugClient {
+    /// 已缓存的源码描述
+    pub fn cached_sources(&self) -> Vec<SourceForm> {
+        self.shared.sources.lock().unwrap().forms.values().cloned().collect()
+    }
+
+    /// 按source actor名称或URL查找已缓存的源码
+    pub fn find_source(&self, id: &str) -> Option<SourceClient> {
+        let form = self.shared.sources.lock().unwrap().find(id)?.clone();
+        Some(SourceClient::new(&self.shared, form))
+    }
+
+    /// 获取 `location` 所在行前后各 `context` 行代码，返回 `(行号, 内容)`，行号从1开始
+    ///
+    /// `location.actor` 可以是source actor名称或URL，需要先通过 `sources` 或 `newSource` 事件缓存。
+    pub async fn source_lines(&self, location: &Location, context: u32) -> Result<Vec<(u32, String)>> {
+        let Some(source) = self.find_source(&location.actor) else {
+            return Err(ProtocolError::Protocol(format!("unknown source {}", location.actor)).into());
+        };
+        let text = source.source().await?.text;
+        let first = location.line.saturating_sub(context).max(1);
+        let last = location.line.saturating_add(context);
+        Ok(text
+            .lines()
+            .zip(1..)
+            .filter(|(_, line)| (first..=last).contains(line))
+            .map(|(content, line)| (line, content.to_string()))
+            .collect())
+    }
+}
+
+/// Source actor句柄
+#[derive(Clone)]
+pub struct SourceClient {
+    form: SourceForm,
+    shared: Arc<Shared>,
+}
+
+impl SourceClient {
+    pub(super) fn new(shared: &Arc<Shared>, form: SourceForm) -> Self {
+        Self {
+            form,
+            shared: shared.clone(),
+        }
+    }
+
+    pub fn actor(&self) -> &str {
+        &self.form.actor
+    }
+
+    pub fn form(&self) -> &SourceForm {
+        &self.form
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        self.form.url.as_deref()
+    }
+
+    async fn request<T: serde::de::DeserializeOwned>(&self, request: SourceRequest) -> Result<T> {
+        self.shared
+            .request(&self.form.actor, serde_json::to_value(request)?)
+            .await?
+            .parse()
+    }
+
+    /// 获取完整的源码文本，结果会被缓存
+    pub async fn source(&self) -> Result<SourceText> {
+        if let Some(text) = self.shared.sources.lock().unwrap().texts.get(&self.form.actor) {
+            return Ok(text.clone());
+        }
+        let reply: SourceReply = self.request(SourceRequest::Source).await?;
+        let text = match reply.source {
+            Grip::String(text) => text,
+            Grip::LongString(grip) => LongString::new(&self.shared, grip).fetch_full().await?,
+            other => {
+                return Err(ProtocolError::Protocol(format!("unexpected source text {:?}", other)).into());
+            }
+        };
+        let text = SourceText {
+            text,
+            content_type: reply.content_type,
+        };
+        self.shared
+            .sources
+            .lock()
+            .unwrap()
+            .texts
+            .insert(self.form.actor.clone(), text.clone());
+        Ok(text)
+    }
+
+    /// 可以设置断点的行号
+    pub async fn get_breakable_lines(&self) -> Result<Vec<u32>> {
+        let reply: BreakableLinesReply = self.request(SourceRequest::GetBreakableLines).await?;
+        Ok(reply.lines)
+    }
+
+    /// 可以设置断点的位置：行号到列号列表，`query` 限定范围
+    pub async fn get_breakpoint_positions_compressed(
+        &self,
+        query: Option<PositionQuery>,
+    ) -> Result<BTreeMap<u32, Vec<u32>>> {
+        let reply: BreakpointPositionsReply = self
+            .request(SourceRequest::GetBreakpointPositionsCompressed { query })
+            .await?;
+        Ok(reply.positions)
+    }
+}
@@ -0,0 +1,197 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use tokio::io::DuplexStream;
+use tokio::sync::mpsc;
+
+use crate::protocol::{BulkHeader, Message};
+
+/// 每个订阅默认缓冲的事件数量
+pub const DEFAULT_EVENT_BUFFER: usize = 256;
+
+/// 事件订阅：按actor和事件类型过滤后的事件流
+///
+/// 后台读取任务从不等待订阅者：缓冲区满时新到达的事件会被丢弃，
+/// 丢弃的数量可以通过 `dropped` 查询。连接关闭后流结束。
+pub struct Subscription {
+    rx: mpsc::Receiver<Message>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Subscription {
+    /// 等待下一个事件，连接关闭后返回 `None`
+    pub async fn recv(&mut self) -> Option<Message> {
+        self.rx.recv().await
+    }
+
+    /// 不等待地取出已到达的事件
+    pub fn try_recv(&mut self) -> Option<Message> {
+        self.rx.try_recv().ok()
+    }
+
+    /// 因缓冲区已满而丢弃的事件数量
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Message;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Message>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// 收到的bulk数据包，负载通过 `body` 以流的方式读取
+///
+/// 后台读取任务会等待负载被读完才继续处理后续数据包，
+/// 因此收到后应尽快读取或丢弃 `body`。
+pub struct BulkStream {
+    pub header: BulkHeader,
+    pub body: DuplexStream,
+}
+
+struct EventSubscriber {
+    actor: Option<String>,
+    event_types: Option<Vec<String>>,
+    tx: mpsc::Sender<Message>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventSubscriber {
+    fn matches(&self, actor: &str, event_type: Option<&str>) -> bool {
+        self.actor.as_deref().is_none_or(|a| a == actor)
+            && self
+                .event_types
+                .as_ref()
+                .is_none_or(|types| event_type.is_some_and(|t| types.iter().any(|e| e == t)))
+    }
+
+    /// 不等待地发送事件，缓冲区满时丢弃并计数；订阅已关闭时返回 `false`
+    fn send(&self, msg: &Message) -> bool {
+        match self.tx.try_send(msg.clone()) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                log::debug!("Subscriber lagging, dropped event {:?} from {}", msg.packet_type(), msg.from);
+                true
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+}
+
+struct BulkSubscriber {
+    actor: Option<String>,
+    packet_type: Option<String>,
+    tx: mpsc::Sender<BulkStream>,
+}
+
+/// 后台读取任务使用的订阅者列表
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    events: Vec<EventSubscriber>,
+    /// 只接收没有其他订阅者匹配的事件
+    fallback: Option<EventSubscriber>,
+    bulk: Vec<BulkSubscriber>,
+}
+
+impl Subscribers {
+    /// 添加事件订阅，`None` 表示不按该字段过滤
+    pub(crate) fn subscribe(
+        &mut self,
+        actor: Option<String>,
+        event_type: Option<String>,
+        capacity: usize,
+    ) -> Subscription {
+        self.subscribe_types(actor, event_type.map(|t| vec![t]), capacity)
+    }
+
+    /// 添加事件订阅，匹配 `event_types` 中的任意一种事件类型
+    pub(crate) fn subscribe_types(
+        &mut self,
+        actor: Option<String>,
+        event_types: Option<Vec<String>>,
+        capacity: usize,
+    ) -> Subscription {
+        let (tx, rx) = mpsc::channel(capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+        self.events.push(EventSubscriber {
+            actor,
+            event_types,
+            tx,
+            dropped: dropped.clone(),
+        });
+        Subscription { rx, dropped }
+    }
+
+    /// 设置兜底订阅，只接收没有其他订阅者匹配的事件；会替换之前的兜底订阅
+    pub(crate) fn subscribe_fallback(&mut self, capacity: usize) -> Subscription {
+        let (tx, rx) = mpsc::channel(capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+        self.fallback = Some(EventSubscriber {
+            actor: None,
+            event_types: None,
+            tx,
+            dropped: dropped.clone(),
+        });
+        Subscription { rx, dropped }
+    }
+
+    /// 添加bulk数据包订阅
+    pub(crate) fn subscribe_bulk(
+        &mut self,
+        actor: Option<String>,
+        packet_type: Option<String>,
+        capacity: usize,
+    ) -> mpsc::Receiver<BulkStream> {
+        let (tx, rx) = mpsc::channel(capacity);
+        self.bulk.push(BulkSubscriber { actor, packet_type, tx });
+        rx
+    }
+
+    /// 将事件分发给所有匹配的订阅者，没有订阅者匹配时交给兜底订阅，并清理已关闭的订阅
+    pub(crate) fn dispatch(&mut self, msg: &Message) {
+        let event_type = msg.packet_type();
+        let mut matched = false;
+        self.events.retain(|sub| {
+            if !sub.matches(&msg.from, event_type) {
+                return !sub.tx.is_closed();
+            }
+            let open = sub.send(msg);
+            matched |= open;
+            open
+        });
+        if !matched && let Some(fallback) = &self.fallback && !fallback.send(msg) {
+            self.fallback = None;
+        }
+    }
+
+    /// 为bulk数据包找到第一个匹配且仍有空位的订阅者，返回负载的写入端
+    pub(crate) fn dispatch_bulk(&mut self, header: &BulkHeader) -> Option<DuplexStream> {
+        self.bulk.retain(|sub| !sub.tx.is_closed());
+        let sub = self.bulk.iter().find(|sub| {
+            sub.actor.as_deref().is_none_or(|a| a == header.actor)
+                && sub.packet_type.as_deref().is_none_or(|t| t == header.packet_type)
+        })?;
+        let (writer, body) = tokio::io::duplex(64 * 1024);
+        match sub.tx.try_send(BulkStream { header: header.clone(), body }) {
+            Ok(()) => Some(writer),
+            Err(_) => {
+                log::debug!("Bulk subscriber lagging, dropped {} from {}", header.packet_type, header.actor);
+                None
+            }
+        }
+    }
+
+    /// 连接关闭时结束所有订阅
+    pub(crate) fn close(&mut self) {
+        self.events.clear();
+        self.fallback = None;
+        self.bulk.clear();
+    }
+}
@@ -0,0 +1,340 @@
+//! 从tab描述符到target及其子actor的客户端句柄
+//!
+//! 描述符的 `getTarget` 返回target描述，其中列出console、thread等子actor；
+//! `Target` 为这些子actor构造可以独立克隆和使用的句柄。
+
+use anyhow::Result;
+use serde_json::Value;
+use std::sync::Arc;
+
+use super::{long_string, SelectedTab, Shared, SourceClient, Subscription, DEFAULT_EVENT_BUFFER};
+use crate::protocol::thread::{Breakpoint, BreakpointLocation, Environment, Frame};
+use crate::protocol::messages::{
+    ConsoleRequest, EvaluationResult, FrameRequest, FramesReply, GetTargetReply, ListTabsReply, ReloadOptions,
+    ResumeLimit, RootRequest, SetBreakpointReply, SourceForm, SourcesReply, StartedListenersReply, TabDescriptor,
+    TabEvent, TabRequest, TargetForm, ThreadOptions, ThreadRequest,
+};
+use crate::protocol::{Message, ProtocolError};
+
+/// 对tab描述符调用 `getTarget`
+pub(super) async fn get_target(shared: &Arc<Shared>, descriptor: &TabDescriptor) -> Result<Target> {
+    let reply: GetTargetReply = shared
+        .request(&descriptor.actor, serde_json::to_value(TabRequest::GetTarget)?)
+        .await?
+        .parse()?;
+    Ok(Target::new(shared, descriptor.clone(), reply.frame))
+}
+
+/// 通过 `listTabs` 按URL查找tab，获取其target并attach线程，记录为当前选中的tab
+pub(super) async fn select_tab(shared: &Arc<Shared>, url: &str) -> Result<Target> {
+    let list: ListTabsReply = shared
+        .request("root", serde_json::to_value(RootRequest::ListTabs)?)
+        .await?
+        .parse()?;
+    let Some(descriptor) = list.tabs.into_iter().find(|tab| tab.url == url) else {
+        return Err(ProtocolError::Protocol(format!("no tab with url {}", url)).into());
+    };
+    let target = get_target(shared, &descriptor).await?;
+    target.thread.attach().await?;
+    *shared.selected_tab.lock().unwrap() = Some(SelectedTab {
+        url: url.to_string(),
+        actor: descriptor.actor,
+        target_actor: target.form.actor.clone(),
+        thread_actor: target.form.thread_actor.clone(),
+        console_actor: target.form.console_actor.clone(),
+    });
+    Ok(target)
+}
+
+/// 已获取的target及其子actor句柄
+#[derive(Clone)]
+pub struct Target {
+    pub descriptor: TabDescriptor,
+    pub form: TargetForm,
+    pub thread: ThreadClient,
+    pub console: ConsoleClient,
+    shared: Arc<Shared>,
+}
+
+/// 一次完成的导航
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Navigation {
+    pub url: String,
+    pub title: Option<String>,
+    /// 重新加载时为 `reload` 的选项
+    pub reload: Option<ReloadOptions>,
+}
+
+impl Target {
+    fn new(shared: &Arc<Shared>, descriptor: TabDescriptor, form: TargetForm) -> Self {
+        shared.sources.lock().unwrap().track_target(&form.actor, &form.thread_actor);
+        Self {
+            thread: ThreadClient {
+                actor: form.thread_actor.clone(),
+                shared: shared.clone(),
+            },
+            console: ConsoleClient {
+                actor: form.console_actor.clone(),
+                shared: shared.clone(),
+            },
+            descriptor,
+            form,
+            shared: shared.clone(),
+        }
+    }
+
+    /// target actor的名称
+    pub fn actor(&self) -> &str {
+        &self.form.actor
+    }
+
+    /// 导航到 `url` 并等待页面加载完成
+    pub async fn navigate_to(&self, url: &str) -> Result<Navigation> {
+        self.navigate(TabRequest::NavigateTo { url: url.to_string() }).await
+    }
+
+    /// 重新加载页面并等待加载完成，`force` 表示跳过缓存
+    pub async fn reload(&self, force: bool) -> Result<Navigation> {
+        self.navigate(TabRequest::Reload {
+            options: ReloadOptions { force },
+        })
+        .await
+    }
+
+    pub async fn go_back(&self) -> Result<Navigation> {
+        self.navigate(TabRequest::GoBack).await
+    }
+
+    pub async fn go_forward(&self) -> Result<Navigation> {
+        self.navigate(TabRequest::GoForward).await
+    }
+
+    pub async fn detach(&self) -> Result<()> {
+        self.shared
+            .request(&self.form.actor, serde_json::to_value(TabRequest::Detach)?)
+            .await?;
+        Ok(())
+    }
+
+    /// 订阅该target的 `tabNavigated` 事件，可配合 `wait_for_navigation` 等待页面加载完成
+    pub fn subscribe_navigation(&self) -> Subscription {
+        self.shared.subscribers.lock().unwrap().subscribe(
+            Some(self.form.actor.clone()),
+            Some("tabNavigated".to_string()),
+            DEFAULT_EVENT_BUFFER,
+        )
+    }
+
+    /// 发送导航请求并等待完成；设置了请求超时时，等待导航同样受其限制
+    async fn navigate(&self, request: TabRequest) -> Result<Navigation> {
+        // 导航事件可能先于回复到达，因此先订阅
+        let mut events = self.subscribe_navigation();
+        self.shared
+            .request(&self.form.actor, serde_json::to_value(request)?)
+            .await?;
+        let Some(timeout) = self.shared.request_timeout else {
+            return wait_for_navigation(&mut events).await;
+        };
+        match tokio::time::timeout(timeout, wait_for_navigation(&mut events)).await {
+            Ok(navigation) => navigation,
+            Err(_) => Err(ProtocolError::Timeout {
+                actor: self.form.actor.clone(),
+                packet_type: "tabNavigated".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// 等待 `tabNavigated` 事件中表示加载完成的一个（忽略 `state` 为 `start` 的事件）
+pub async fn wait_for_navigation(events: &mut Subscription) -> Result<Navigation> {
+    while let Some(msg) = events.recv().await {
+        if let Ok(TabEvent::TabNavigated { url, title, state, reload, .. }) = msg.parse()
+            && state.as_deref() != Some("start")
+        {
+            return Ok(Navigation { url, title, reload });
+        }
+    }
+    Err(ProtocolError::Protocol("connection closed while waiting for navigation".to_string()).into())
+}
+
+/// Thread actor句柄
+#[derive(Clone)]
+pub struct ThreadClient {
+    actor: String,
+    shared: Arc<Shared>,
+}
+
+impl ThreadClient {
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    /// 向thread actor发送任意请求
+    pub async fn request(&self, packet: Value) -> Result<Message> {
+        self.shared.request(&self.actor, packet).await
+    }
+
+    async fn request_typed(&self, request: ThreadRequest) -> Result<Message> {
+        self.request(serde_json::to_value(request)?).await
+    }
+
+    pub async fn attach(&self) -> Result<Message> {
+        self.request_typed(ThreadRequest::Attach {
+            options: ThreadOptions::default(),
+        })
+        .await
+    }
+
+    pub async fn detach(&self) -> Result<Message> {
+        self.request_typed(ThreadRequest::Detach).await
+    }
+
+    pub async fn interrupt(&self) -> Result<Message> {
+        self.request_typed(ThreadRequest::Interrupt).await
+    }
+
+    pub async fn resume(&self) -> Result<Message> {
+        self.resume_with_limit(None, None).await
+    }
+
+    /// 恢复执行，`limit` 为单步限制，执行到限制处后以 `resumeLimit` 原因推送 `paused` 事件
+    pub async fn resume_with_limit(&self, limit: Option<ResumeLimit>, frame: Option<&Frame>) -> Result<Message> {
+        let request = ThreadRequest::Resume {
+            resume_limit: limit,
+            frame_actor_id: frame.map(|frame| frame.actor.clone()),
+        };
+        self.request_typed(request).await
+    }
+
+    /// 单步跳过
+    pub async fn step_over(&self) -> Result<Message> {
+        self.resume_with_limit(Some(ResumeLimit::Next), None).await
+    }
+
+    /// 单步进入
+    pub async fn step_in(&self) -> Result<Message> {
+        self.resume_with_limit(Some(ResumeLimit::Step), None).await
+    }
+
+    /// 单步跳出
+    pub async fn step_out(&self) -> Result<Message> {
+        self.resume_with_limit(Some(ResumeLimit::Finish), None).await
+    }
+
+    /// 从头重新执行 `frame`
+    pub async fn restart_frame(&self, frame: &Frame) -> Result<Message> {
+        self.resume_with_limit(Some(ResumeLimit::Restart), Some(frame)).await
+    }
+
+    /// 暂停时获取调用栈，从栈顶第 `start` 帧开始最多 `count` 帧
+    pub async fn frames(&self, start: u32, count: u32) -> Result<Vec<Frame>> {
+        let reply: FramesReply = self.request_typed(ThreadRequest::Frames { start, count }).await?.parse()?;
+        Ok(reply.frames)
+    }
+
+    /// 获取某一帧的作用域链，`frame` 为 `frames` 返回的帧
+    pub async fn get_environment(&self, frame: &Frame) -> Result<Environment> {
+        self.shared
+            .request(&frame.actor, serde_json::to_value(FrameRequest::GetEnvironment)?)
+            .await?
+            .parse()
+    }
+
+    /// 配置抛出异常时是否暂停，`ignore_caught` 表示忽略会被捕获的异常
+    pub async fn pause_on_exceptions(&self, pause: bool, ignore_caught: bool) -> Result<()> {
+        let options = ThreadOptions {
+            pause_on_exceptions: pause,
+            ignore_caught_exceptions: ignore_caught,
+        };
+        self.request_typed(ThreadRequest::Reconfigure { options }).await?;
+        Ok(())
+    }
+
+    /// 设置断点，回复中带有断点actor和实际生效的位置；不会记录到本地debugger actor
+    pub async fn set_breakpoint(&self, breakpoint: &Breakpoint) -> Result<SetBreakpointReply> {
+        let request = ThreadRequest::SetBreakpoint {
+            location: breakpoint.location.clone(),
+            options: breakpoint.options.clone(),
+        };
+        self.request_typed(request).await?.parse()
+    }
+
+    pub async fn remove_breakpoint(&self, location: &BreakpointLocation) -> Result<()> {
+        self.request_typed(ThreadRequest::RemoveBreakpoint {
+            location: location.clone(),
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// 列出线程中已加载的源码，并更新本地源码缓存
+    pub async fn sources(&self) -> Result<Vec<SourceForm>> {
+        let reply: SourcesReply = self.request_typed(ThreadRequest::Sources).await?.parse()?;
+        let mut cache = self.shared.sources.lock().unwrap();
+        for source in &reply.sources {
+            cache.insert(&self.actor, source.clone());
+        }
+        Ok(reply.sources)
+    }
+
+    /// 为源码描述构造source actor句柄
+    pub fn source(&self, form: SourceForm) -> SourceClient {
+        SourceClient::new(&self.shared, form)
+    }
+
+    /// 订阅该线程的事件，例如 `paused`
+    pub fn subscribe(&self, event_type: &str) -> Subscription {
+        self.shared.subscribers.lock().unwrap().subscribe(
+            Some(self.actor.clone()),
+            Some(event_type.to_string()),
+            DEFAULT_EVENT_BUFFER,
+        )
+    }
+}
+
+/// Console actor句柄
+#[derive(Clone)]
+pub struct ConsoleClient {
+    actor: String,
+    shared: Arc<Shared>,
+}
+
+impl ConsoleClient {
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    /// 向console actor发送任意请求
+    pub async fn request(&self, packet: Value) -> Result<Message> {
+        self.shared.request(&self.actor, packet).await
+    }
+
+    /// 开启监听，例如 `["PageError", "ConsoleAPI"]`
+    pub async fn start_listeners(&self, listeners: &[&str]) -> Result<StartedListenersReply> {
+        let request = ConsoleRequest::StartListeners {
+            listeners: listeners.iter().map(|listener| listener.to_string()).collect(),
+        };
+        self.request(serde_json::to_value(request)?).await?.parse()
+    }
+
+    /// 同步求值表达式；结果为长字符串时按 `ClientOptions::long_string_limit` 自动获取完整内容
+    pub async fn evaluate_js(&self, text: &str) -> Result<EvaluationResult> {
+        let request = ConsoleRequest::EvaluateJs {
+            text: text.to_string(),
+            frame_actor: None,
+        };
+        let mut evaluation: EvaluationResult = self.request(serde_json::to_value(request)?).await?.parse()?;
+        long_string::resolve(&self.shared, &mut evaluation.result).await?;
+        Ok(evaluation)
+    }
+
+    /// 订阅该console的事件，例如 `consoleAPICall`
+    pub fn subscribe(&self, event_type: &str) -> Subscription {
+        self.shared.subscribers.lock().unwrap().subscribe(
+            Some(self.actor.clone()),
+            Some(event_type.to_string()),
+            DEFAULT_EVENT_BUFFER,
+        )
+    }
+}
@@ -0,0 +1,232 @@
+//! Watcher actor：新版Firefox通过watcher统一推送target和资源
+//!
+//! `watchResources` 之后，控制台消息、网络事件、源码、错误等以
+//! `resource-available-*`、`resource-updated-*`、`resource-destroyed-*` 事件送达，
+//! 不再需要对各个actor分别调用 `startListeners`。
+
+use anyhow::Result;
+use futures::Stream;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use super::{Shared, Subscription, DEFAULT_EVENT_BUFFER};
+use crate::protocol::messages::{
+    Resource, ResourceType, TabDescriptor, TabRequest, TargetForm, WatcherEvent, WatcherForm, WatcherRequest,
+};
+use crate::protocol::Message;
+
+const RESOURCE_EVENTS: [&str; 6] = [
+    "resource-available-form",
+    "resource-available-array",
+    "resource-updated-form",
+    "resource-updated-array",
+    "resource-destroyed-form",
+    "resource-destroyed-array",
+];
+
+/// 对tab描述符调用 `getWatcher`
+pub(super) async fn get_watcher(shared: &Arc<Shared>, descriptor: &TabDescriptor) -> Result<WatcherClient> {
+    let form: WatcherForm = shared
+        .request(&descriptor.actor, serde_json::to_value(TabRequest::GetWatcher)?)
+        .await?
+        .parse()?;
+    Ok(WatcherClient {
+        form,
+        shared: shared.clone(),
+    })
+}
+
+/// Watcher actor句柄
+#[derive(Clone)]
+pub struct WatcherClient {
+    form: WatcherForm,
+    shared: Arc<Shared>,
+}
+
+impl WatcherClient {
+    pub fn actor(&self) -> &str {
+        &self.form.actor
+    }
+
+    /// watcher是否支持监听某种资源
+    pub fn supports_resource(&self, resource_type: &ResourceType) -> bool {
+        let Ok(Value::String(name)) = serde_json::to_value(resource_type) else {
+            return false;
+        };
+        self.form.traits["resources"][name.as_str()] == Value::Bool(true)
+    }
+
+    /// 开始监听某类target（例如 `"frame"`、`"worker"`），返回服务器立即报告的已有targets
+    ///
+    /// 之后出现的target以 `target-available-form` 事件送达，可通过 `DebugClient::subscribe` 订阅。
+    pub async fn watch_targets(&self, target_type: &str) -> Result<Vec<TargetForm>> {
+        // 已有的targets在回复之前送达，因此先订阅再发送请求
+        let mut events = self.shared.subscribers.lock().unwrap().subscribe(
+            Some(self.form.actor.clone()),
+            Some("target-available-form".to_string()),
+            DEFAULT_EVENT_BUFFER,
+        );
+        let request = WatcherRequest::WatchTargets {
+            target_type: target_type.to_string(),
+        };
+        self.shared
+            .request(&self.form.actor, serde_json::to_value(request)?)
+            .await?;
+
+        let mut targets = Vec::new();
+        while let Some(msg) = events.try_recv() {
+            if let Ok(WatcherEvent::TargetAvailableForm { target }) = msg.parse() {
+                targets.push(target);
+            }
+        }
+        Ok(targets)
+    }
+
+    pub async fn unwatch_targets(&self, target_type: &str) -> Result<()> {
+        let request = WatcherRequest::UnwatchTargets {
+            target_type: target_type.to_string(),
+        };
+        self.shared
+            .request(&self.form.actor, serde_json::to_value(request)?)
+            .await?;
+        Ok(())
+    }
+
+    /// 开始监听资源，返回只包含这些资源类型的资源流
+    ///
+    /// 已有的资源在回复之前送达，同样会出现在返回的流中。
+    pub async fn watch_resources(&self, resource_types: &[ResourceType]) -> Result<ResourceStream> {
+        let events = self.shared.subscribers.lock().unwrap().subscribe_types(
+            None,
+            Some(RESOURCE_EVENTS.iter().map(|t| t.to_string()).collect()),
+            DEFAULT_EVENT_BUFFER,
+        );
+        let request = WatcherRequest::WatchResources {
+            resource_types: resource_types.to_vec(),
+        };
+        self.shared
+            .request(&self.form.actor, serde_json::to_value(request)?)
+            .await?;
+        Ok(ResourceStream {
+            events,
+            resource_types: resource_types.to_vec(),
+            queue: VecDeque::new(),
+        })
+    }
+
+    pub async fn unwatch_resources(&self, resource_types: &[ResourceType]) -> Result<()> {
+        let request = WatcherRequest::UnwatchResources {
+            resource_types: resource_types.to_vec(),
+        };
+        self.shared
+            .request(&self.form.actor, serde_json::to_value(request)?)
+            .await?;
+        Ok(())
+    }
+}
+
+/// 资源变化的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceChange {
+    Available,
+    Updated,
+    Destroyed,
+}
+
+/// 资源流中的一项
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceEvent {
+    pub change: ResourceChange,
+    /// 发出事件的actor（watcher或target）
+    pub from: String,
+    pub resource: Resource,
+}
+
+/// `watch_resources` 返回的资源流；一个事件数据包中的多个资源会被拆开逐个返回
+pub struct ResourceStream {
+    events: Subscription,
+    resource_types: Vec<ResourceType>,
+    queue: VecDeque<ResourceEvent>,
+}
+
+impl ResourceStream {
+    /// 等待下一个资源，连接关闭后返回 `None`
+    pub async fn recv(&mut self) -> Option<ResourceEvent> {
+        loop {
+            if let Some(event) = self.queue.pop_front() {
+                return Some(event);
+            }
+            let msg = self.events.recv().await?;
+            self.push(&msg);
+        }
+    }
+
+    /// 因缓冲区已满而丢弃的事件数据包数量
+    pub fn dropped(&self) -> u64 {
+        self.events.dropped()
+    }
+
+    fn push(&mut self, msg: &Message) {
+        let change = match msg.packet_type() {
+            Some("resource-available-form" | "resource-available-array") => ResourceChange::Available,
+            Some("resource-updated-form" | "resource-updated-array") => ResourceChange::Updated,
+            Some("resource-destroyed-form" | "resource-destroyed-array") => ResourceChange::Destroyed,
+            _ => return,
+        };
+        for resource in parse_resources(&msg.content) {
+            if self.resource_types.contains(&resource.resource_type) {
+                self.queue.push_back(ResourceEvent {
+                    change,
+                    from: msg.from.clone(),
+                    resource,
+                });
+            }
+        }
+    }
+}
+
+impl Stream for ResourceStream {
+    type Item = ResourceEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ResourceEvent>> {
+        loop {
+            if let Some(event) = self.queue.pop_front() {
+                return Poll::Ready(Some(event));
+            }
+            match Pin::new(&mut self.events).poll_next(cx) {
+                Poll::Ready(Some(msg)) => self.push(&msg),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// 解析资源事件：`*-form` 为 `{"resources": [...]}`，
+/// `*-array` 为 `{"array": [[resourceType, [...]], ...]}`，其中的资源不带 `resourceType`
+fn parse_resources(content: &Value) -> Vec<Resource> {
+    let mut resources = Vec::new();
+    if let Some(list) = content.get("resources").and_then(Value::as_array) {
+        resources.extend(list.iter().filter_map(|value| serde_json::from_value(value.clone()).ok()));
+    }
+    if let Some(groups) = content.get("array").and_then(Value::as_array) {
+        for group in groups {
+            let (Some(resource_type), Some(list)) = (group.get(0), group.get(1).and_then(Value::as_array)) else {
+                continue;
+            };
+            for value in list {
+                let mut value = value.clone();
+                if let Some(object) = value.as_object_mut() {
+                    object.insert("resourceType".to_string(), resource_type.clone());
+                }
+                if let Ok(resource) = serde_json::from_value(value) {
+                    resources.push(resource);
+                }
+            }
+        }
+    }
+    resources
+}
@@ -0,0 +1,10 @@
+pub mod protocol;
+pub mod client;
+pub mod actors;
+pub mod server;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use client::DebugClient;
+pub use protocol::Message;
+pub use server::DevToolsServer;
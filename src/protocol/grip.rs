@@ -0,0 +1,235 @@
+//! Grip：RDP中值的表示
+//!
+//! 布尔值、有限数字和短字符串直接用JSON表示；`undefined`、`null`、`NaN`、`-0` 等无法用JSON表示的值，
+//! 以及对象、长字符串、symbol都是带 `type` 字段的JSON对象，后三者由服务器上的actor持有。
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+/// 一个值的grip
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Grip {
+    #[default]
+    Undefined,
+    Null,
+    NaN,
+    Infinity,
+    NegativeInfinity,
+    NegativeZero,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    /// BigInt的十进制文本
+    BigInt(String),
+    Symbol(SymbolGrip),
+    LongString(LongStringGrip),
+    Object(ObjectGrip),
+    /// 无法识别的grip，保留原始JSON
+    Other(Value),
+}
+
+/// 对象grip，`actor` 为对应的object actor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectGrip {
+    pub actor: String,
+    pub class: String,
+    #[serde(default)]
+    pub extensible: bool,
+    #[serde(default)]
+    pub frozen: bool,
+    #[serde(default)]
+    pub sealed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub own_property_length: Option<u64>,
+    /// 服务器生成的预览，格式取决于对象的类型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<Value>,
+}
+
+/// 长字符串grip，只带有开头的一部分内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LongStringGrip {
+    pub actor: String,
+    pub initial: String,
+    pub length: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolGrip {
+    pub actor: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// 属性描述符：数据属性带 `value`，访问器属性带 `get`/`set`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PropertyDescriptor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Grip>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub get: Option<Grip>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set: Option<Grip>,
+    #[serde(default)]
+    pub configurable: bool,
+    #[serde(default)]
+    pub enumerable: bool,
+    #[serde(default)]
+    pub writable: bool,
+}
+
+impl PropertyDescriptor {
+    /// 值为 `value` 的可写、可枚举、可配置数据属性
+    pub fn data(value: Grip) -> Self {
+        Self {
+            value: Some(value),
+            configurable: true,
+            enumerable: true,
+            writable: true,
+            ..Self::default()
+        }
+    }
+}
+
+impl Grip {
+    /// 持有该值的actor，原始值没有actor
+    pub fn actor(&self) -> Option<&str> {
+        match self {
+            Grip::Symbol(symbol) => Some(&symbol.actor),
+            Grip::LongString(long_string) => Some(&long_string.actor),
+            Grip::Object(object) => Some(&object.actor),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&ObjectGrip> {
+        match self {
+            Grip::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    pub fn as_long_string(&self) -> Option<&LongStringGrip> {
+        match self {
+            Grip::LongString(long_string) => Some(long_string),
+            _ => None,
+        }
+    }
+
+    /// 按JavaScript规则判断真值；无法识别的grip视为真
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Grip::Undefined | Grip::Null | Grip::NaN | Grip::NegativeZero => false,
+            Grip::Bool(value) => *value,
+            Grip::Number(value) => *value != 0.0,
+            Grip::String(value) => !value.is_empty(),
+            Grip::BigInt(text) => text.trim_start_matches('-') != "0",
+            Grip::LongString(long_string) => long_string.length > 0,
+            _ => true,
+        }
+    }
+
+    /// 转换为RDP使用的JSON表示
+    pub fn to_value(&self) -> Value {
+        let typed = |type_name: &str, fields: Value| {
+            let mut object = match fields {
+                Value::Object(object) => object,
+                _ => Map::new(),
+            };
+            object.insert("type".to_string(), Value::String(type_name.to_string()));
+            Value::Object(object)
+        };
+        match self {
+            Grip::Undefined => typed("undefined", Value::Null),
+            Grip::Null => typed("null", Value::Null),
+            Grip::NaN => typed("NaN", Value::Null),
+            Grip::Infinity => typed("Infinity", Value::Null),
+            Grip::NegativeInfinity => typed("-Infinity", Value::Null),
+            Grip::NegativeZero => typed("-0", Value::Null),
+            Grip::Bool(value) => Value::Bool(*value),
+            Grip::Number(value) => serde_json::Number::from_f64(*value).map_or(Value::Null, Value::Number),
+            Grip::String(value) => Value::String(value.clone()),
+            Grip::BigInt(text) => typed("BigInt", serde_json::json!({ "text": text })),
+            Grip::Symbol(symbol) => typed("symbol", serde_json::to_value(symbol).unwrap_or_default()),
+            Grip::LongString(long_string) => typed("longString", serde_json::to_value(long_string).unwrap_or_default()),
+            Grip::Object(object) => typed("object", serde_json::to_value(object).unwrap_or_default()),
+            Grip::Other(value) => value.clone(),
+        }
+    }
+
+    /// 从RDP使用的JSON表示解析，无法识别的值保存为 `Grip::Other`
+    pub fn from_value(value: Value) -> Self {
+        let type_name = match &value {
+            Value::Null => return Grip::Null,
+            Value::Bool(value) => return Grip::Bool(*value),
+            Value::Number(number) => return number.as_f64().map_or(Grip::Other(value.clone()), Grip::Number),
+            Value::String(value) => return Grip::String(value.clone()),
+            Value::Array(_) => return Grip::Other(value),
+            Value::Object(object) => match object.get("type").and_then(Value::as_str) {
+                Some(type_name) => type_name.to_string(),
+                None => return Grip::Other(value),
+            },
+        };
+        let parsed = match type_name.as_str() {
+            "undefined" => Some(Grip::Undefined),
+            "null" => Some(Grip::Null),
+            "NaN" => Some(Grip::NaN),
+            "Infinity" => Some(Grip::Infinity),
+            "-Infinity" => Some(Grip::NegativeInfinity),
+            "-0" => Some(Grip::NegativeZero),
+            "BigInt" => value["text"].as_str().map(|text| Grip::BigInt(text.to_string())),
+            "symbol" => serde_json::from_value(value.clone()).ok().map(Grip::Symbol),
+            "longString" => serde_json::from_value(value.clone()).ok().map(Grip::LongString),
+            "object" => serde_json::from_value(value.clone()).ok().map(Grip::Object),
+            _ => None,
+        };
+        parsed.unwrap_or(Grip::Other(value))
+    }
+}
+
+impl From<bool> for Grip {
+    fn from(value: bool) -> Self {
+        Grip::Bool(value)
+    }
+}
+
+impl From<f64> for Grip {
+    fn from(value: f64) -> Self {
+        if value.is_nan() {
+            Grip::NaN
+        } else if value == f64::INFINITY {
+            Grip::Infinity
+        } else if value == f64::NEG_INFINITY {
+            Grip::NegativeInfinity
+        } else if value == 0.0 && value.is_sign_negative() {
+            Grip::NegativeZero
+        } else {
+            Grip::Number(value)
+        }
+    }
+}
+
+impl From<&str> for Grip {
+    fn from(value: &str) -> Self {
+        Grip::String(value.to_string())
+    }
+}
+
+impl From<ObjectGrip> for Grip {
+    fn from(object: ObjectGrip) -> Self {
+        Grip::Object(object)
+    }
+}
+
+impl Serialize for Grip {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Grip {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Value::deserialize(deserializer).map(Grip::from_value)
+    }
+}
@@ -0,0 +1,913 @@
+//! 按actor类型划分的强类型协议消息
+//!
+//! 请求和事件使用 `type` 字段作为serde标签；回复大多不带 `type`，因此用普通结构体表示。
+//! 无法识别的数据包仍可以通过未类型化的 `Message` 处理。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::grip::{Grip, PropertyDescriptor};
+use super::thread::{BreakpointLocation, BreakpointOptions, Frame, Location};
+use super::Message;
+
+/// 连接建立后服务器发送的hello数据包
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerInfo {
+    pub application_type: String,
+    #[serde(default)]
+    pub traits: Value,
+}
+
+impl ServerInfo {
+    /// 服务器是否声明了某个特性，值为 `false` 或 `null` 视为不支持
+    pub fn has_trait(&self, name: &str) -> bool {
+        !matches!(self.traits.get(name), None | Some(Value::Null) | Some(Value::Bool(false)))
+    }
+}
+
+/// Root actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum RootRequest {
+    ListTabs,
+    /// 不指定 `browser_id` 时返回当前选中的tab
+    GetTab {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        browser_id: Option<u64>,
+    },
+    ListProcesses,
+    ListWorkers,
+    ListServiceWorkerRegistrations,
+    ListAddons,
+    GetRoot,
+}
+
+/// `listTabs` 等请求返回的tab描述
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabDescriptor {
+    pub actor: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browser_id: Option<u64>,
+    #[serde(default, rename = "browsingContextID", skip_serializing_if = "Option::is_none")]
+    pub browsing_context_id: Option<u64>,
+    #[serde(default, rename = "outerWindowID", skip_serializing_if = "Option::is_none")]
+    pub outer_window_id: Option<u64>,
+    /// 旧版服务器在tab描述中直接给出console actor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub console_actor: Option<String>,
+    #[serde(default)]
+    pub selected: bool,
+}
+
+/// `listTabs` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTabsReply {
+    pub tabs: Vec<TabDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected: Option<u32>,
+}
+
+/// `getTab` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetTabReply {
+    pub tab: TabDescriptor,
+}
+
+/// 进程描述
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessDescriptor {
+    pub actor: String,
+    pub id: u64,
+    #[serde(default)]
+    pub is_parent: bool,
+}
+
+/// `listProcesses` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListProcessesReply {
+    pub processes: Vec<ProcessDescriptor>,
+}
+
+/// worker描述
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerDescriptor {
+    pub actor: String,
+    #[serde(default)]
+    pub url: String,
+    /// worker类型：0为专用worker，1为共享worker，2为service worker
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub worker_type: Option<u32>,
+}
+
+/// `listWorkers` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListWorkersReply {
+    pub workers: Vec<WorkerDescriptor>,
+}
+
+/// service worker注册描述
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceWorkerRegistrationDescriptor {
+    pub actor: String,
+    pub scope: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+/// `listServiceWorkerRegistrations` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListServiceWorkerRegistrationsReply {
+    pub registrations: Vec<ServiceWorkerRegistrationDescriptor>,
+}
+
+/// 扩展描述
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddonDescriptor {
+    pub actor: String,
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub debuggable: bool,
+}
+
+/// `listAddons` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListAddonsReply {
+    pub addons: Vec<AddonDescriptor>,
+}
+
+/// `getRoot` 的回复：root上的全局actors，未列出的actors保留在 `other` 中
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RootForm {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_actor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preference_actor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addons_actor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub perf_actor: Option<String>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, Value>,
+}
+
+/// Root actor事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum RootEvent {
+    TabListChanged,
+    AddonListChanged,
+    WorkerListChanged,
+    ProcessListChanged,
+    ServiceWorkerRegistrationListChanged,
+}
+
+/// Tab/target actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum TabRequest {
+    Attach,
+    Detach,
+    GetTarget,
+    GetWatcher,
+    NavigateTo {
+        url: String,
+    },
+    Reload {
+        #[serde(default)]
+        options: ReloadOptions,
+    },
+    GoBack,
+    GoForward,
+}
+
+/// `reload` 请求的选项
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReloadOptions {
+    /// 跳过缓存重新加载
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Tab `attach` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabAttachedReply {
+    pub thread_actor: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// `getTarget` 返回的target描述，包含该target下的各子actor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetForm {
+    pub actor: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub url: String,
+    pub console_actor: String,
+    pub thread_actor: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inspector_actor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style_sheets_actor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accessibility_actor: Option<String>,
+    /// 其他子actors和target特性
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, Value>,
+}
+
+/// Tab描述符 `getTarget` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetTargetReply {
+    pub frame: TargetForm,
+}
+
+/// Tab/target actor事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum TabEvent {
+    TabNavigated {
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state: Option<String>,
+        #[serde(default)]
+        is_frame_switching: bool,
+        /// 由 `reload` 触发时为其选项
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reload: Option<ReloadOptions>,
+    },
+    #[serde(rename = "will-navigate")]
+    WillNavigate {
+        #[serde(rename = "newURI", alias = "url")]
+        new_uri: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reload: Option<ReloadOptions>,
+    },
+    TabDetached,
+    FrameUpdate {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        frames: Option<Vec<Value>>,
+    },
+}
+
+/// Tab描述符 `getWatcher` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatcherForm {
+    pub actor: String,
+    #[serde(default)]
+    pub traits: Value,
+}
+
+/// Watcher actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum WatcherRequest {
+    WatchTargets { target_type: String },
+    UnwatchTargets { target_type: String },
+    WatchResources { resource_types: Vec<ResourceType> },
+    UnwatchResources { resource_types: Vec<ResourceType> },
+}
+
+/// Watcher actor事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", rename_all_fields = "camelCase")]
+pub enum WatcherEvent {
+    TargetAvailableForm { target: TargetForm },
+    TargetDestroyedForm { target: TargetForm },
+}
+
+/// watcher可以监听的资源类型
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResourceType {
+    ConsoleMessage,
+    ErrorMessage,
+    PlatformMessage,
+    NetworkEvent,
+    NetworkEventStacktrace,
+    Source,
+    Stylesheet,
+    DocumentEvent,
+    ThreadState,
+    Cookies,
+    LocalStorage,
+    SessionStorage,
+    IndexedDb,
+    CssChange,
+    Reflow,
+    /// 本库尚未列出的资源类型
+    #[serde(untagged)]
+    Other(String),
+}
+
+/// 资源事件中的一个资源；各类型特有的字段保存在 `fields` 中，可用 `parse` 解析为具体类型
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub resource_type: ResourceType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_id: Option<Value>,
+    #[serde(flatten)]
+    pub fields: serde_json::Map<String, Value>,
+}
+
+impl Resource {
+    /// 把资源解析为具体的资源结构，例如 `ConsoleMessageResource`
+    pub fn parse<T: serde::de::DeserializeOwned>(&self) -> anyhow::Result<T> {
+        Ok(serde_json::from_value(serde_json::to_value(self)?)?)
+    }
+}
+
+/// `console-message` 资源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleMessageResource {
+    /// 旧版服务器把消息内容嵌套在 `message` 字段中
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column_number: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_stamp: Option<f64>,
+}
+
+/// `error-message` 资源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorMessageResource {
+    pub page_error: Value,
+}
+
+/// `network-event` 资源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkEventResource {
+    pub actor: String,
+    pub url: String,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_date_time: Option<String>,
+    #[serde(default, rename = "isXHR")]
+    pub is_xhr: bool,
+}
+
+/// 源码描述，出现在线程的 `sources` 回复、`newSource` 事件和 `source` 资源中
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceForm {
+    pub actor: String,
+    /// eval等动态生成的代码没有URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, rename = "sourceMapURL", skip_serializing_if = "Option::is_none")]
+    pub source_map_url: Option<String>,
+    #[serde(default)]
+    pub is_black_boxed: bool,
+}
+
+/// `source` 资源
+pub type SourceResource = SourceForm;
+
+/// Thread actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ThreadRequest {
+    Attach {
+        #[serde(default)]
+        options: ThreadOptions,
+    },
+    Reconfigure {
+        options: ThreadOptions,
+    },
+    Detach,
+    Interrupt,
+    Resume {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        resume_limit: Option<ResumeLimit>,
+        /// `restart` 时重新执行的帧
+        #[serde(default, rename = "frameActorID", skip_serializing_if = "Option::is_none")]
+        frame_actor_id: Option<String>,
+    },
+    Frames {
+        start: u32,
+        count: u32,
+    },
+    Sources,
+    SetBreakpoint {
+        location: BreakpointLocation,
+        options: BreakpointOptions,
+    },
+    RemoveBreakpoint {
+        location: BreakpointLocation,
+    },
+}
+
+/// Thread `setBreakpoint` 的回复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetBreakpointReply {
+    /// 断点actor，出现在命中时 `paused` 事件的 `why.actors` 中
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    /// 断点滑动到可断点位置后实际生效的位置；URL匹配的源码尚未加载时为空
+    #[serde(default)]
+    pub actual_locations: Vec<Location>,
+}
+
+/// `resume` 请求的单步限制，执行到限制处后以 `resumeLimit` 原因暂停
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ResumeLimit {
+    /// 单步跳过：执行到当前帧或调用者中的下一条语句
+    Next,
+    /// 单步进入：执行到下一条语句，包括被调用的函数
+    Step,
+    /// 单步跳出：执行到当前帧返回
+    Finish,
+    /// 从头重新执行 `frameActorID` 指定的帧
+    Restart,
+}
+
+/// 线程配置，用于 `attach` 和 `reconfigure`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadOptions {
+    /// 抛出异常时暂停
+    #[serde(default)]
+    pub pause_on_exceptions: bool,
+    /// 暂停于异常时忽略会被捕获的异常
+    #[serde(default)]
+    pub ignore_caught_exceptions: bool,
+}
+
+/// `paused` 事件中的暂停原因
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum PauseReason {
+    /// 命中断点，`actors` 为命中的断点actor
+    Breakpoint {
+        #[serde(default)]
+        actors: Vec<String>,
+    },
+    /// 断点条件求值时抛出异常
+    BreakpointConditionThrown {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    Exception {
+        exception: Grip,
+    },
+    DebuggerStatement,
+    /// 单步执行结束
+    ResumeLimit,
+    Interrupted {
+        /// 在下一条语句处暂停，而不是立即暂停
+        #[serde(default)]
+        on_next: bool,
+    },
+    EventBreakpoint {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        breakpoint: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    #[serde(rename = "XHR")]
+    Xhr,
+    /// 无法识别的原因
+    #[serde(other)]
+    Other,
+}
+
+/// Thread actor事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ThreadEvent {
+    Paused {
+        /// 旧版服务器的pause actor
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        actor: Option<String>,
+        /// 栈顶帧
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        frame: Option<Box<Frame>>,
+        why: PauseReason,
+    },
+    Resumed,
+    NewSource {
+        source: SourceForm,
+    },
+}
+
+/// Thread `frames` 的回复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FramesReply {
+    #[serde(default)]
+    pub frames: Vec<Frame>,
+}
+
+/// Frame actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FrameRequest {
+    GetEnvironment,
+}
+
+/// Object actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ObjectRequest {
+    PrototypeAndProperties,
+    OwnPropertyNames,
+    GetPrototype,
+    EnumProperties {
+        options: EnumPropertiesOptions,
+    },
+}
+
+/// `enumProperties` 的选项
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnumPropertiesOptions {
+    /// 只列出数组下标属性
+    #[serde(default)]
+    pub ignore_non_indexed_properties: bool,
+    /// 只列出非数组下标属性
+    #[serde(default)]
+    pub ignore_indexed_properties: bool,
+    #[serde(default)]
+    pub sort: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+}
+
+/// Object `prototypeAndProperties` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrototypeAndPropertiesReply {
+    #[serde(default)]
+    pub prototype: Grip,
+    #[serde(default)]
+    pub own_properties: BTreeMap<String, PropertyDescriptor>,
+    /// 可以安全调用的getter的值
+    #[serde(default)]
+    pub safe_getter_values: BTreeMap<String, Value>,
+}
+
+/// Object `ownPropertyNames` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnPropertyNamesReply {
+    #[serde(default)]
+    pub own_property_names: Vec<String>,
+}
+
+/// Object `getPrototype` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrototypeReply {
+    #[serde(default)]
+    pub prototype: Grip,
+}
+
+/// Object `enumProperties` 返回的属性迭代器
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropertyIteratorForm {
+    pub actor: String,
+    pub count: u64,
+}
+
+/// Object `enumProperties` 的回复
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnumPropertiesReply {
+    pub iterator: PropertyIteratorForm,
+}
+
+/// Property iterator actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum PropertyIteratorRequest {
+    Slice {
+        start: u64,
+        count: u64,
+    },
+    All,
+}
+
+/// Property iterator `slice`/`all` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyIteratorSlice {
+    #[serde(default)]
+    pub own_properties: BTreeMap<String, PropertyDescriptor>,
+}
+
+/// Long string actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LongStringRequest {
+    Substring {
+        start: u64,
+        end: u64,
+    },
+}
+
+/// Long string `substring` 的回复
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubstringReply {
+    pub substring: String,
+}
+
+/// Thread `sources` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourcesReply {
+    #[serde(default)]
+    pub sources: Vec<SourceForm>,
+}
+
+/// Source actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SourceRequest {
+    Source,
+    GetBreakableLines,
+    GetBreakpointPositionsCompressed {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        query: Option<PositionQuery>,
+    },
+}
+
+/// 限定 `getBreakpointPositionsCompressed` 的范围，行列均包含起点、不包含终点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PositionQuery {
+    pub start: QueryPosition,
+    pub end: QueryPosition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryPosition {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Source `source` 的回复，较长的源码以长字符串返回
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceReply {
+    pub source: Grip,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+/// Source `getBreakableLines` 的回复
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreakableLinesReply {
+    #[serde(default)]
+    pub lines: Vec<u32>,
+}
+
+/// Source `getBreakpointPositionsCompressed` 的回复：行号到可设置断点的列号列表
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreakpointPositionsReply {
+    #[serde(default)]
+    pub positions: BTreeMap<u32, Vec<u32>>,
+}
+
+/// Console actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ConsoleRequest {
+    StartListeners {
+        listeners: Vec<String>,
+    },
+    StopListeners {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        listeners: Option<Vec<String>>,
+    },
+    GetCachedMessages {
+        message_types: Vec<String>,
+    },
+    #[serde(rename = "evaluateJS")]
+    EvaluateJs {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        frame_actor: Option<String>,
+    },
+    #[serde(rename = "evaluateJSAsync")]
+    EvaluateJsAsync {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        frame_actor: Option<String>,
+    },
+}
+
+/// Console `startListeners` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartedListenersReply {
+    #[serde(default)]
+    pub started_listeners: Vec<String>,
+    #[serde(default, rename = "nativeConsoleAPI")]
+    pub native_console_api: bool,
+}
+
+/// Console `evaluateJS` 的回复，以及 `evaluateJSAsync` 的 `evaluationResult` 事件内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationResult {
+    #[serde(default, rename = "resultID", skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    #[serde(default)]
+    pub result: Grip,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exception: Option<Grip>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exception_message: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<f64>,
+}
+
+/// Console actor事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ConsoleEvent {
+    #[serde(rename = "consoleAPICall")]
+    ConsoleApiCall {
+        message: Value,
+    },
+    PageError {
+        page_error: Value,
+    },
+    EvaluationResult(Box<EvaluationResult>),
+}
+
+/// Network event actor请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum NetworkEventRequest {
+    GetRequestHeaders,
+    GetRequestPostData,
+    GetResponseHeaders,
+    GetResponseContent,
+    GetEventTimings,
+}
+
+/// Network event `getResponseContent` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseContentReply {
+    pub content: ResponseContent,
+    /// 响应正文未被保存时为 `true`
+    #[serde(default)]
+    pub content_discarded: bool,
+}
+
+/// 响应正文，`text` 可能是长字符串
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseContent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub text: Grip,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// 二进制内容为 `base64`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+/// Network event `getRequestPostData` 的回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestPostDataReply {
+    pub post_data: PostData,
+    #[serde(default)]
+    pub post_data_discarded: bool,
+}
+
+/// 请求正文，`text` 可能是长字符串
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostData {
+    #[serde(default)]
+    pub text: Grip,
+}
+
+/// `networkEvent` 事件中描述请求的表单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkEventForm {
+    pub actor: String,
+    pub url: String,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_date_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<Value>,
+    #[serde(default, rename = "isXHR")]
+    pub is_xhr: bool,
+}
+
+/// `networkEventUpdate` 中 `responseStart` 携带的响应摘要
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkResponseStart {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<String>,
+    pub status: String,
+    pub status_text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers_size: Option<u64>,
+}
+
+/// Network事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum NetworkEvent {
+    NetworkEvent {
+        event_actor: NetworkEventForm,
+    },
+    NetworkEventUpdate {
+        update_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        response: Option<NetworkResponseStart>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_size: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        total_time: Option<f64>,
+    },
+}
+
+/// 已识别的事件；无法识别的数据包保留为原始 `Message`
+#[derive(Debug, Clone)]
+pub enum Event {
+    Root(RootEvent),
+    Tab(TabEvent),
+    Thread(ThreadEvent),
+    Console(ConsoleEvent),
+    Network(NetworkEvent),
+    Watcher(WatcherEvent),
+    Unknown(Message),
+}
+
+impl Event {
+    /// 按 `type` 字段把消息解析为强类型事件
+    pub fn from_message(msg: Message) -> Self {
+        fn parse<T: serde::de::DeserializeOwned>(msg: &Message) -> Option<T> {
+            serde_json::from_value(msg.content.clone()).ok()
+        }
+
+        if let Some(event) = parse(&msg) {
+            return Event::Root(event);
+        }
+        if let Some(event) = parse(&msg) {
+            return Event::Tab(event);
+        }
+        if let Some(event) = parse(&msg) {
+            return Event::Thread(event);
+        }
+        if let Some(event) = parse(&msg) {
+            return Event::Console(event);
+        }
+        if let Some(event) = parse(&msg) {
+            return Event::Network(event);
+        }
+        if let Some(event) = parse(&msg) {
+            return Event::Watcher(event);
+        }
+        Event::Unknown(msg)
+    }
+}
@@ -1,7 +1,14 @@
-use serde::{Deserialize, Serialize};
+pub mod grip;
+pub mod messages;
+pub mod recording;
+pub mod thread;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::io::{Read, Write};
 use std::net::TcpStream;
 use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 /// 基本的JSON数据包特征
 pub trait JsonPacketStream {
@@ -18,30 +25,393 @@ impl JsonPacketStream for TcpStream {
     }
 
     fn read_json_packet(&mut self) -> Result<Option<serde_json::Value>> {
-        let mut buffer = vec![];
+        let limits = PacketLimits::default();
+        let mut header = HeaderParser::new(&limits);
         loop {
             let mut buf = [0];
             let byte = match self.read(&mut buf) {
-                Ok(0) => return Ok(None), // EOF
+                Ok(0) if header.is_empty() => return Ok(None), // EOF
+                Ok(0) => return Err(ProtocolError::UnexpectedEof.into()),
                 Ok(1) => buf[0],
                 Ok(_) => unreachable!(),
                 Err(e) => return Err(e.into()),
             };
             match byte {
                 b':' => {
-                    let packet_len_str = String::from_utf8(buffer)?;
-                    let packet_len = packet_len_str.parse::<u64>()?;
-                    let mut packet = String::new();
-                    self.take(packet_len).read_to_string(&mut packet)?;
+                    let packet_len = match header.finish()? {
+                        PacketHeader::Json(len) => len,
+                        PacketHeader::Bulk(header) => {
+                            return Err(ProtocolError::Protocol(format!(
+                                "unexpected bulk packet from {}: {}",
+                                header.actor, header.packet_type
+                            ))
+                            .into());
+                        }
+                    };
+                    let mut packet = vec![0; packet_len];
+                    self.read_exact(&mut packet).map_err(map_eof)?;
+                    let packet = String::from_utf8(packet)?;
                     log::debug!("<- {}", packet);
                     return Ok(Some(serde_json::from_str(&packet)?));
                 },
-                c => buffer.push(c),
+                c => header.push(&[c])?,
+            }
+        }
+    }
+}
+
+/// 数据包大小限制，防止恶意或有缺陷的对端导致任意大小的内存分配
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacketLimits {
+    /// JSON数据包长度前缀允许的最大位数
+    pub max_header_digits: usize,
+    /// JSON数据包的最大字节数
+    pub max_json_packet_size: usize,
+    /// bulk数据包负载的最大字节数
+    pub max_bulk_packet_size: u64,
+}
+
+impl Default for PacketLimits {
+    fn default() -> Self {
+        Self {
+            max_header_digits: 10,
+            max_json_packet_size: 64 * 1024 * 1024,
+            max_bulk_packet_size: 4 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// bulk包头（不含长度前缀外的 `bulk ` 部分）的最大字节数
+const MAX_BULK_HEADER_LEN: usize = 1024;
+
+/// 解析出的包头
+enum PacketHeader {
+    Json(usize),
+    Bulk(BulkHeader),
+}
+
+/// 增量解析包头，边读边校验，保证包头占用的内存有上界
+struct HeaderParser<'a> {
+    limits: &'a PacketLimits,
+    buf: Vec<u8>,
+}
+
+impl<'a> HeaderParser<'a> {
+    fn new(limits: &'a PacketLimits) -> Self {
+        Self { limits, buf: Vec::new() }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// 追加包头字节（不含结尾的 `:`）
+    fn push(&mut self, bytes: &[u8]) -> Result<(), ProtocolError> {
+        self.buf.extend_from_slice(bytes);
+        if self.buf.starts_with(b"bulk ") {
+            if self.buf.len() > MAX_BULK_HEADER_LEN {
+                return Err(ProtocolError::HeaderTooLong { limit: MAX_BULK_HEADER_LEN });
+            }
+        } else if !b"bulk ".starts_with(&self.buf) {
+            if let Some(&byte) = self.buf.iter().find(|b| !b.is_ascii_digit()) {
+                return Err(ProtocolError::InvalidHeaderByte(byte));
+            }
+            if self.buf.len() > self.limits.max_header_digits {
+                return Err(ProtocolError::HeaderTooLong { limit: self.limits.max_header_digits });
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<PacketHeader> {
+        if let Some(rest) = self.buf.strip_prefix(b"bulk ") {
+            let header = parse_bulk_header(std::str::from_utf8(rest)?)?;
+            if header.length > self.limits.max_bulk_packet_size {
+                return Err(ProtocolError::PacketTooLarge {
+                    size: header.length,
+                    limit: self.limits.max_bulk_packet_size,
+                }
+                .into());
+            }
+            return Ok(PacketHeader::Bulk(header));
+        }
+        if self.buf.is_empty() || !self.buf.iter().all(u8::is_ascii_digit) {
+            // 只剩空包头或 `bulk ` 的前缀（如 `bu`），此时结尾的 `:` 就是非法字节
+            return Err(ProtocolError::InvalidHeaderByte(b':').into());
+        }
+        // 位数已受限，不会溢出u64
+        let len: u64 = std::str::from_utf8(&self.buf)?.parse()?;
+        if len > self.limits.max_json_packet_size as u64 {
+            return Err(ProtocolError::PacketTooLarge {
+                size: len,
+                limit: self.limits.max_json_packet_size as u64,
             }
+            .into());
         }
+        Ok(PacketHeader::Json(len as usize))
+    }
+}
+
+/// 把读取负载时的 `UnexpectedEof` 转换为 `ProtocolError::UnexpectedEof`
+fn map_eof(e: std::io::Error) -> anyhow::Error {
+    if e.kind() == std::io::ErrorKind::UnexpectedEof {
+        ProtocolError::UnexpectedEof.into()
+    } else {
+        e.into()
     }
 }
 
+/// 可用作异步传输的双向字节流（TcpStream、UnixStream、DuplexStream等）
+pub trait AsyncStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> AsyncStream for S {}
+
+/// bulk数据包头：`bulk <actor> <type> <length>:`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkHeader {
+    pub actor: String,
+    pub packet_type: String,
+    pub length: u64,
+}
+
+/// 传输层数据包：JSON数据包或bulk数据包
+#[derive(Debug)]
+pub enum Packet {
+    Json(serde_json::Value),
+    /// bulk数据包只读取包头，负载仍留在流中，需通过 `copy_bulk_payload` 读出
+    Bulk(BulkHeader),
+}
+
+/// 异步JSON数据包特征，适用于任意带缓冲的 AsyncRead + AsyncWrite 传输
+#[async_trait]
+pub trait AsyncJsonPacketStream {
+    async fn write_json_packet<T: Serialize + Sync>(&mut self, obj: &T) -> Result<()>;
+    async fn read_json_packet(&mut self) -> Result<Option<serde_json::Value>>;
+
+    /// 读取下一个数据包（JSON或bulk）
+    async fn read_packet(&mut self) -> Result<Option<Packet>>;
+
+    /// 将bulk数据包的负载流式写入 `sink`，返回写入的字节数
+    async fn copy_bulk_payload<W>(&mut self, header: &BulkHeader, sink: &mut W) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized;
+
+    /// 发送bulk数据包，负载从 `source` 中流式读取 `header.length` 字节
+    async fn write_bulk_packet<R>(&mut self, header: &BulkHeader, source: &mut R) -> Result<()>
+    where
+        R: AsyncRead + Unpin + Send + ?Sized;
+}
+
+#[async_trait]
+impl<S> AsyncJsonPacketStream for S
+where
+    S: AsyncBufRead + AsyncWrite + Unpin + Send,
+{
+    async fn write_json_packet<T: Serialize + Sync>(&mut self, obj: &T) -> Result<()> {
+        write_json_packet(self, obj).await
+    }
+
+    async fn read_json_packet(&mut self) -> Result<Option<serde_json::Value>> {
+        match read_packet(self).await? {
+            Some(Packet::Json(value)) => Ok(Some(value)),
+            Some(Packet::Bulk(header)) => {
+                // 丢弃负载以保持流同步
+                copy_bulk_payload(self, &header, &mut tokio::io::sink()).await?;
+                Err(ProtocolError::Protocol(format!(
+                    "unexpected bulk packet from {}: {}",
+                    header.actor, header.packet_type
+                ))
+                .into())
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn read_packet(&mut self) -> Result<Option<Packet>> {
+        read_packet(self).await
+    }
+
+    async fn copy_bulk_payload<W>(&mut self, header: &BulkHeader, sink: &mut W) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        copy_bulk_payload(self, header, sink).await
+    }
+
+    async fn write_bulk_packet<R>(&mut self, header: &BulkHeader, source: &mut R) -> Result<()>
+    where
+        R: AsyncRead + Unpin + Send + ?Sized,
+    {
+        write_bulk_packet(self, header, source).await
+    }
+}
+
+/// 向写半部发送一个JSON数据包，可用于 `tokio::io::split` 拆分后的流
+pub async fn write_json_packet<W, T>(writer: &mut W, obj: &T) -> Result<()>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+    T: Serialize + ?Sized,
+{
+    let s = serde_json::to_string(obj)?;
+    log::debug!("-> {}", s);
+    writer.write_all(format!("{}:{}", s.len(), s).as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// 从读半部读取下一个数据包（JSON或bulk），使用默认的大小限制
+pub async fn read_packet<R>(reader: &mut R) -> Result<Option<Packet>>
+where
+    R: AsyncBufRead + Unpin + ?Sized,
+{
+    read_packet_with_limits(reader, &PacketLimits::default()).await
+}
+
+/// 从读半部读取下一个数据包，超出 `limits` 的包头或数据包会被拒绝
+pub async fn read_packet_with_limits<R>(reader: &mut R, limits: &PacketLimits) -> Result<Option<Packet>>
+where
+    R: AsyncBufRead + Unpin + ?Sized,
+{
+    let mut header = HeaderParser::new(limits);
+    loop {
+        let buf = reader.fill_buf().await?;
+        if buf.is_empty() {
+            if header.is_empty() {
+                return Ok(None); // EOF
+            }
+            return Err(ProtocolError::UnexpectedEof.into());
+        }
+        match buf.iter().position(|&b| b == b':') {
+            Some(pos) => {
+                header.push(&buf[..pos])?;
+                reader.consume(pos + 1);
+                break;
+            }
+            None => {
+                let len = buf.len();
+                header.push(buf)?;
+                reader.consume(len);
+            }
+        }
+    }
+
+    match header.finish()? {
+        PacketHeader::Bulk(header) => {
+            log::debug!("<- bulk {} {} {}", header.actor, header.packet_type, header.length);
+            Ok(Some(Packet::Bulk(header)))
+        }
+        PacketHeader::Json(packet_len) => {
+            let mut packet = vec![0; packet_len];
+            reader.read_exact(&mut packet).await.map_err(map_eof)?;
+            let packet = String::from_utf8(packet)?;
+            log::debug!("<- {}", packet);
+            Ok(Some(Packet::Json(serde_json::from_str(&packet)?)))
+        }
+    }
+}
+
+/// 将bulk数据包的负载从读半部流式写入 `sink`
+pub async fn copy_bulk_payload<R, W>(reader: &mut R, header: &BulkHeader, sink: &mut W) -> Result<u64>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let copied = tokio::io::copy(&mut reader.take(header.length), sink).await?;
+    if copied != header.length {
+        return Err(ProtocolError::UnexpectedEof.into());
+    }
+    Ok(copied)
+}
+
+/// 向写半部发送bulk数据包，负载从 `source` 中流式读取 `header.length` 字节
+pub async fn write_bulk_packet<W, R>(writer: &mut W, header: &BulkHeader, source: &mut R) -> Result<()>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+    R: AsyncRead + Unpin + ?Sized,
+{
+    log::debug!("-> bulk {} {} {}", header.actor, header.packet_type, header.length);
+    writer
+        .write_all(format!("bulk {} {} {}:", header.actor, header.packet_type, header.length).as_bytes())
+        .await?;
+    let copied = tokio::io::copy(&mut source.take(header.length), writer).await?;
+    if copied != header.length {
+        return Err(ProtocolError::Protocol(format!(
+            "bulk source ended after {} of {} bytes",
+            copied, header.length
+        ))
+        .into());
+    }
+    writer.flush().await?;
+    Ok(())
+}
+
+/// 解析 `bulk ` 之后的包头部分：`<actor> <type> <length>`
+fn parse_bulk_header(header: &str) -> Result<BulkHeader> {
+    let mut parts = header.split(' ');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(actor), Some(packet_type), Some(length), None)
+            if !actor.is_empty() && !packet_type.is_empty() =>
+        {
+            Ok(BulkHeader {
+                actor: actor.to_string(),
+                packet_type: packet_type.to_string(),
+                length: parse_bulk_length(length)?,
+            })
+        }
+        _ => Err(ProtocolError::Protocol(format!("malformed bulk header: {}", header)).into()),
+    }
+}
+
+/// 解析bulk包头中的负载长度；与JSON包头一样，非数字字节报告为 `InvalidHeaderByte`，缺少长度时报告结尾的 `:`
+fn parse_bulk_length(length: &str) -> Result<u64, ProtocolError> {
+    if let Some(byte) = length.bytes().find(|byte| !byte.is_ascii_digit()) {
+        return Err(ProtocolError::InvalidHeaderByte(byte));
+    }
+    length.parse().map_err(|_| match length {
+        "" => ProtocolError::InvalidHeaderByte(b':'),
+        _ => ProtocolError::Protocol(format!("bulk length {} is out of range", length)),
+    })
+}
+
+/// 服务器主动推送的事件类型，这些数据包永远不会被当作请求的回复
+pub const UNSOLICITED_EVENTS: &[&str] = &[
+    "addonListChanged",
+    "consoleAPICall",
+    "documentEvent",
+    "evaluationResult",
+    "frameUpdate",
+    "inspectObject",
+    "networkEvent",
+    "networkEventUpdate",
+    "newGlobal",
+    "newSource",
+    "pageError",
+    "paused",
+    "processListChanged",
+    "reflowActivity",
+    "resource-available-array",
+    "resource-available-form",
+    "resource-destroyed-array",
+    "resource-destroyed-form",
+    "resource-updated-array",
+    "resource-updated-form",
+    "resumed",
+    "serviceWorkerRegistrationListChanged",
+    "tabDetached",
+    "tabListChanged",
+    "tabNavigated",
+    "target-available-form",
+    "target-destroyed-form",
+    "updatedSource",
+    "will-navigate",
+    "workerListChanged",
+];
+
+/// 判断数据包类型是否为服务器主动推送的事件
+pub fn is_unsolicited_event(packet_type: &str) -> bool {
+    UNSOLICITED_EVENTS.contains(&packet_type)
+}
+
 /// 基本的Actor描述
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ActorDescription {
@@ -58,8 +428,10 @@ pub struct Method {
 }
 
 /// 基本的消息结构
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
+    /// 客户端发出的请求不携带 `from`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub from: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub to: Option<String>,
@@ -67,6 +439,32 @@ pub struct Message {
     pub content: serde_json::Value,
 }
 
+impl Message {
+    /// 由强类型请求构造发往 `to` 的消息
+    pub fn request<T: Serialize>(to: &str, body: &T) -> Result<Self> {
+        Ok(Self {
+            from: String::new(),
+            to: Some(to.to_string()),
+            content: serde_json::to_value(body)?,
+        })
+    }
+
+    /// 是否为连接建立后root actor发送的hello数据包
+    pub fn is_hello(&self) -> bool {
+        self.from == "root" && self.content.get("applicationType").is_some()
+    }
+
+    /// 获取消息的 `type` 字段
+    pub fn packet_type(&self) -> Option<&str> {
+        self.content.get("type").and_then(serde_json::Value::as_str)
+    }
+
+    /// 将消息内容解析为强类型结构
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_value(self.content.clone())?)
+    }
+}
+
 /// 错误类型
 #[derive(Debug, thiserror::Error)]
 pub enum ProtocolError {
@@ -78,4 +476,32 @@ pub enum ProtocolError {
     
     #[error("Protocol error: {0}")]
     Protocol(String),
+
+    #[error("Invalid byte {0:#04x} in packet header")]
+    InvalidHeaderByte(u8),
+
+    #[error("Packet header exceeds {limit} bytes")]
+    HeaderTooLong { limit: usize },
+
+    #[error("Packet of {size} bytes exceeds the limit of {limit} bytes")]
+    PacketTooLarge { size: u64, limit: u64 },
+
+    #[error("Connection closed in the middle of a packet")]
+    UnexpectedEof,
+
+    #[error("Peer is not an RDP server: {0}")]
+    NotRdpServer(String),
+
+    #[error("Request {packet_type} to {actor} timed out")]
+    Timeout {
+        actor: String,
+        packet_type: String,
+    },
+
+    #[error("Actor {actor} replied with error {error}: {}", message.as_deref().unwrap_or(""))]
+    ActorError {
+        actor: String,
+        error: String,
+        message: Option<String>,
+    },
 } 
\ No newline at end of file
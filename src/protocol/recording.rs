@@ -0,0 +1,241 @@
+//! 会话录制与回放
+//!
+//! `Recorder` 把每个收发的数据包连同方向和单调时间戳写入JSON-lines文件，bulk负载以base64保存；
+//! `Replay` 读取录制文件，在一条流上扮演服务器，把录制的服务器数据包按原顺序发回客户端。
+
+use anyhow::{bail, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader as StdBufReader, BufWriter as StdBufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::io::{BufReader, BufWriter, DuplexStream};
+use tokio::task::JoinHandle;
+
+use super::{AsyncStream, BulkHeader, Packet};
+
+/// 数据包方向，以客户端视角区分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// 录制的数据包内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum RecordedPacket {
+    Json {
+        packet: Value,
+    },
+    Bulk {
+        actor: String,
+        packet_type: String,
+        length: u64,
+        /// base64编码的负载；旧版本的录制文件没有负载，无法回放
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body: Option<String>,
+    },
+}
+
+impl RecordedPacket {
+    fn bulk(header: BulkHeader, body: &[u8]) -> Self {
+        RecordedPacket::Bulk {
+            actor: header.actor,
+            packet_type: header.packet_type,
+            length: header.length,
+            body: Some(BASE64.encode(body)),
+        }
+    }
+}
+
+/// 录制文件中的一行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub direction: Direction,
+    /// 自录制开始以来的微秒数
+    pub time_us: u64,
+    #[serde(flatten)]
+    pub packet: RecordedPacket,
+}
+
+/// 把收发的数据包写入JSON-lines文件
+pub struct Recorder {
+    start: Instant,
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Recorder {
+    /// 创建（覆盖）录制文件
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(StdBufWriter::new(File::create(path)?)))
+    }
+
+    /// 录制到任意写入目标
+    pub fn new<W: Write + Send + 'static>(out: W) -> Self {
+        Self {
+            start: Instant::now(),
+            out: Mutex::new(Box::new(out)),
+        }
+    }
+
+    pub fn record_json(&self, direction: Direction, packet: &Value) {
+        self.record(direction, RecordedPacket::Json { packet: packet.clone() });
+    }
+
+    /// 录制bulk数据包，`body` 为完整的负载
+    pub fn record_bulk(&self, direction: Direction, header: &BulkHeader, body: &[u8]) {
+        self.record(direction, RecordedPacket::bulk(header.clone(), body));
+    }
+
+    fn record(&self, direction: Direction, packet: RecordedPacket) {
+        let record = Record {
+            direction,
+            time_us: self.start.elapsed().as_micros() as u64,
+            packet,
+        };
+        let mut out = self.out.lock().unwrap();
+        // 录制失败不应中断调试会话
+        let result = serde_json::to_writer(&mut *out, &record)
+            .map_err(std::io::Error::from)
+            .and_then(|_| out.write_all(b"\n"))
+            .and_then(|_| out.flush());
+        if let Err(e) = result {
+            log::warn!("Failed to record packet: {}", e);
+        }
+    }
+}
+
+/// 读取整个录制文件
+pub fn load_records<P: AsRef<Path>>(path: P) -> Result<Vec<Record>> {
+    let mut records = Vec::new();
+    for line in StdBufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            records.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(records)
+}
+
+/// 回放录制的会话，在流上扮演服务器
+///
+/// 录制中客户端收到的数据包会按原顺序发回；遇到客户端发送的记录时，
+/// 先等待客户端发出对应的数据包再继续，因此回放与客户端的行为保持同步。
+pub struct Replay {
+    records: Vec<Record>,
+    strict: bool,
+    realtime: bool,
+}
+
+impl Replay {
+    pub fn new(records: Vec<Record>) -> Self {
+        Self {
+            records,
+            strict: false,
+            realtime: false,
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(load_records(path)?))
+    }
+
+    /// 严格模式下客户端发送的数据包必须与录制完全一致，否则回放失败；
+    /// 默认只比较 `to` 和 `type`，不一致时仅记录警告
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// 按录制时的时间间隔发送数据包，默认尽快发送
+    pub fn realtime(mut self, realtime: bool) -> Self {
+        self.realtime = realtime;
+        self
+    }
+
+    /// 在给定的流上运行回放
+    pub fn serve<S: AsyncStream + 'static>(self, stream: S) -> JoinHandle<Result<()>> {
+        tokio::spawn(self.run(stream))
+    }
+
+    /// 在内存duplex上运行回放，返回供客户端使用的另一端
+    pub fn duplex(self) -> (DuplexStream, JoinHandle<Result<()>>) {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        (client, self.serve(server))
+    }
+
+    async fn run<S: AsyncStream>(mut self, stream: S) -> Result<()> {
+        let unreplayable = self.records.iter().find(|record| {
+            record.direction == Direction::Received
+                && matches!(&record.packet, RecordedPacket::Bulk { body: None, .. })
+        });
+        if let Some(record) = unreplayable {
+            bail!("recording has no payload for bulk packet {:?}", record.packet);
+        }
+        let (reader, writer) = tokio::io::split(stream);
+        let mut reader = BufReader::new(reader);
+        let mut writer = BufWriter::new(writer);
+        let mut last_time = 0;
+
+        for record in std::mem::take(&mut self.records) {
+            match (record.direction, record.packet) {
+                (Direction::Received, packet) => {
+                    if self.realtime {
+                        let delay = record.time_us.saturating_sub(last_time);
+                        tokio::time::sleep(Duration::from_micros(delay)).await;
+                    }
+                    match packet {
+                        RecordedPacket::Json { packet } => super::write_json_packet(&mut writer, &packet).await?,
+                        RecordedPacket::Bulk { actor, packet_type, length, body } => {
+                            let body = BASE64.decode(body.unwrap_or_default())?;
+                            let header = BulkHeader { actor, packet_type, length };
+                            super::write_bulk_packet(&mut writer, &header, &mut body.as_slice()).await?;
+                        }
+                    }
+                }
+                (Direction::Sent, expected) => {
+                    let actual = match super::read_packet(&mut reader).await? {
+                        Some(Packet::Json(value)) => RecordedPacket::Json { packet: value },
+                        Some(Packet::Bulk(header)) => {
+                            let mut body = Vec::new();
+                            super::copy_bulk_payload(&mut reader, &header, &mut body).await?;
+                            RecordedPacket::bulk(header, &body)
+                        }
+                        None => bail!("client disconnected before the recording ended"),
+                    };
+                    if !self.matches(&actual, &expected) {
+                        if self.strict {
+                            bail!("client sent {:?}, recording has {:?}", actual, expected);
+                        }
+                        log::warn!("Replay diverged: client sent {:?}, recording has {:?}", actual, expected);
+                    }
+                }
+            }
+            last_time = record.time_us;
+        }
+        Ok(())
+    }
+
+    fn matches(&self, actual: &RecordedPacket, expected: &RecordedPacket) -> bool {
+        match (actual, expected) {
+            (RecordedPacket::Json { packet: actual }, RecordedPacket::Json { packet: expected }) if self.strict => {
+                actual == expected
+            }
+            (RecordedPacket::Json { packet: actual }, RecordedPacket::Json { packet: expected }) => {
+                actual.get("to") == expected.get("to") && actual.get("type") == expected.get("type")
+            }
+            // 旧版本的录制文件没有负载，只比较包头
+            (
+                RecordedPacket::Bulk { actor, packet_type, length, .. },
+                RecordedPacket::Bulk { body: None, actor: a, packet_type: t, length: l },
+            ) => (actor, packet_type, length) == (a, t, l),
+            _ => actual == expected,
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Thread actor使用的调用栈、作用域和断点类型
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::grip::{Grip, PropertyDescriptor};
+
+/// 源码中的位置，帧的 `where` 和断点的实际位置，行号从1开始，列号从0开始
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    /// source actor的名称
+    pub actor: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// 断点位置：按源码URL定位，对URL相同的所有源码生效，包括之后加载的源码
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointLocation {
+    pub source_url: String,
+    pub line: u32,
+    /// 未指定时为该行第一个可以设置断点的位置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointOptions {
+    /// 条件断点：表达式为真时才暂停
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// 日志点：命中时输出表达式的值而不暂停
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub location: BreakpointLocation,
+    #[serde(default)]
+    pub options: BreakpointOptions,
+}
+
+impl Breakpoint {
+    pub fn new(source_url: &str, line: u32, column: Option<u32>) -> Self {
+        Self {
+            location: BreakpointLocation {
+                source_url: source_url.to_string(),
+                line,
+                column,
+            },
+            options: BreakpointOptions::default(),
+        }
+    }
+
+    pub fn with_condition(mut self, condition: &str) -> Self {
+        self.options.condition = Some(condition.to_string());
+        self
+    }
+
+    pub fn with_log_value(mut self, log_value: &str) -> Self {
+        self.options.log_value = Some(log_value.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameType {
+    Call,
+    Eval,
+    Global,
+    Module,
+    Wasmcall,
+}
+
+/// 调用栈中的一帧
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    /// frame actor的名称，`getEnvironment` 请求发送给它
+    pub actor: String,
+    #[serde(rename = "type")]
+    pub frame_type: FrameType,
+    /// 被调用函数的名称，全局代码等没有名称
+    #[serde(default, rename = "displayName", skip_serializing_if = "Option::is_none")]
+    pub callee: Option<String>,
+    #[serde(rename = "where")]
+    pub location: Location,
+    #[serde(default)]
+    pub this: Grip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvironmentType {
+    Function,
+    Block,
+    Object,
+    With,
+}
+
+/// 作用域中的绑定
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bindings {
+    /// 函数参数，每项只含一个参数名以保留参数顺序
+    #[serde(default)]
+    pub arguments: Vec<BTreeMap<String, PropertyDescriptor>>,
+    #[serde(default)]
+    pub variables: BTreeMap<String, PropertyDescriptor>,
+}
+
+/// 词法作用域，`parent` 指向外层作用域
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Environment {
+    pub actor: String,
+    #[serde(rename = "type")]
+    pub env_type: EnvironmentType,
+    /// 函数作用域对应的函数grip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<Grip>,
+    /// 对象和with作用域绑定的对象grip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object: Option<Grip>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bindings: Option<Bindings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Box<Environment>>,
+}
+
+impl Environment {
+    /// 从内到外遍历作用域链
+    pub fn scopes(&self) -> impl Iterator<Item = &Environment> {
+        std::iter::successors(Some(self), |env| env.parent.as_deref())
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::Result;
+use std::net::SocketAddr;
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncWrite, BufReader, BufWriter};
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::actors::{root::RootActor, ActorRegistry};
+use crate::protocol::{self, AsyncStream, Message, Packet, PacketLimits};
+
+/// 可嵌入的RDP服务器，把收到的数据包分发给注册表中的actors
+///
+/// 所有连接共享同一个actor注册表，因此多个客户端看到的是同一个被调试运行时。
+pub struct DevToolsServer {
+    listener: TcpListener,
+    registry: ActorRegistry,
+    limits: PacketLimits,
+}
+
+impl DevToolsServer {
+    /// 监听指定地址，并注册默认的root actor
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> Result<Self> {
+        let registry = ActorRegistry::new();
+        registry.register(RootActor::new());
+        Self::bind_with_registry(addr, registry).await
+    }
+
+    /// 使用已准备好的注册表监听指定地址，注册表中必须包含root actor
+    pub async fn bind_with_registry<A: ToSocketAddrs>(addr: A, registry: ActorRegistry) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self {
+            listener,
+            registry,
+            limits: PacketLimits::default(),
+        })
+    }
+
+    /// 设置接收数据包时的大小限制
+    pub fn with_limits(mut self, limits: PacketLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// 获取actor注册表，用于在服务运行前后注册tab、console等actors
+    pub fn registry(&self) -> &ActorRegistry {
+        &self.registry
+    }
+
+    /// 接受连接并为每个连接启动独立的处理任务
+    pub async fn run(self) -> Result<()> {
+        loop {
+            let (stream, addr) = self.listener.accept().await?;
+            stream.set_nodelay(true)?;
+            log::info!("Accepted debugger connection from {}", addr);
+            let registry = self.registry.clone();
+            let limits = self.limits.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve_connection(registry, stream, &limits).await {
+                    log::error!("Connection {} failed: {}", addr, e);
+                }
+                log::info!("Debugger connection {} closed", addr);
+            });
+        }
+    }
+}
+
+/// 在一个已建立的连接上运行RDP会话：先发送hello，再逐个处理请求直到连接关闭
+///
+/// actors通过 `EventSender` 推送的事件会在请求之间写入连接；
+/// 处理请求期间产生的事件先于该请求的回复发送。可直接用于内存duplex等非TCP传输。
+pub async fn serve_connection<S: AsyncStream + 'static>(
+    registry: ActorRegistry,
+    stream: S,
+    limits: &PacketLimits,
+) -> Result<()> {
+    let (reader, writer) = tokio::io::split(stream);
+    let mut writer = BufWriter::new(writer);
+    let events = registry.subscribe_events();
+
+    let hello = match registry.get::<RootActor>("root").await {
+        Some(root) => root.hello(),
+        None => return Err(protocol::ProtocolError::Protocol("no root actor registered".to_string()).into()),
+    };
+    protocol::write_json_packet(&mut writer, &hello).await?;
+
+    // 读取不能被取消，因此在独立任务中进行，与事件一起在下面的循环中等待
+    let (tx, packets) = mpsc::channel(1);
+    let reader = tokio::spawn(read_requests(BufReader::new(reader), limits.clone(), tx));
+    let result = write_loop(&registry, packets, events, &mut writer).await;
+    // 写入失败或事件通道关闭时读取任务仍在运行，随连接一起结束
+    reader.abort();
+    match reader.await {
+        Ok(read) => result.and(read),
+        Err(e) if e.is_cancelled() => result,
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 处理读取任务转来的请求并写入回复和事件，直到连接关闭或注册表的事件通道关闭
+async fn write_loop<W: AsyncWrite + Unpin>(
+    registry: &ActorRegistry,
+    mut packets: mpsc::Receiver<Value>,
+    mut events: broadcast::Receiver<Message>,
+    writer: &mut W,
+) -> Result<()> {
+    loop {
+        tokio::select! {
+            value = packets.recv() => {
+                let Some(value) = value else {
+                    break;
+                };
+                let reply = match serde_json::from_value::<Message>(value) {
+                    Ok(msg) => dispatch(registry, msg).await,
+                    Err(e) => error_reply("root", "badParameterType", &e.to_string()),
+                };
+                while let Ok(event) = events.try_recv() {
+                    protocol::write_json_packet(writer, &event).await?;
+                }
+                protocol::write_json_packet(writer, &reply).await?;
+            }
+            event = events.recv() => match event {
+                Ok(event) => protocol::write_json_packet(writer, &event).await?,
+                Err(broadcast::error::RecvError::Lagged(n)) => log::warn!("Connection lagging, dropped {} events", n),
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+    Ok(())
+}
+
+/// 读取客户端发来的JSON数据包，bulk数据包被丢弃
+async fn read_requests<R: AsyncBufRead + Unpin>(
+    mut reader: R,
+    limits: PacketLimits,
+    tx: mpsc::Sender<Value>,
+) -> Result<()> {
+    while let Some(packet) = protocol::read_packet_with_limits(&mut reader, &limits).await? {
+        match packet {
+            Packet::Json(value) => {
+                if tx.send(value).await.is_err() {
+                    break;
+                }
+            }
+            Packet::Bulk(header) => {
+                log::warn!("Discarding bulk packet for {}: {}", header.actor, header.packet_type);
+                protocol::copy_bulk_payload(&mut reader, &header, &mut tokio::io::sink()).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 将请求交给 `to` 指定的actor，并把各种失败情况转换为RDP错误回复
+async fn dispatch(registry: &ActorRegistry, msg: Message) -> Message {
+    let Some(to) = msg.to.clone() else {
+        return error_reply("root", "missingParameter", "packet has no 'to' field");
+    };
+    if !registry.contains(&to) {
+        return error_reply(&to, "noSuchActor", &format!("no such actor: {}", to));
+    }
+    let packet_type = msg.packet_type().unwrap_or_default().to_string();
+    match registry.handle_message(msg).await {
+        Ok(Some(mut reply)) => {
+            // 服务器发出的数据包只带 `from`
+            reply.to = None;
+            reply
+        }
+        Ok(None) => error_reply(
+            &to,
+            "unrecognizedPacketType",
+            &format!("actor {} does not recognize the packet type '{}'", to, packet_type),
+        ),
+        Err(e) => error_reply(&to, "unknownError", &e.to_string()),
+    }
+}
+
+fn error_reply(from: &str, error: &str, message: &str) -> Message {
+    Message {
+        from: from.to_string(),
+        to: None,
+        content: serde_json::json!({
+            "error": error,
+            "message": message,
+        }),
+    }
+}
@@ -0,0 +1,203 @@
+//! 用于集成测试的可脚本化RDP模拟服务器
+//!
+//! 脚本按顺序执行：`expect` 等待客户端发来的数据包并检查其内容，
+//! `send`/`reply` 发送回复或事件，`delay` 暂停脚本，`send_after` 在后台延迟发送事件，
+//! 因而可以构造延迟到达或与回复交错的事件。
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{BufReader, BufWriter, DuplexStream};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::protocol::{self, AsyncStream, BulkHeader, Packet};
+
+enum Step {
+    Expect(Value),
+    Send(Value),
+    SendAfter(Duration, Value),
+    SendBulk(BulkHeader, Vec<u8>),
+    Delay(Duration),
+}
+
+enum Outgoing {
+    Json(Value),
+    Bulk(BulkHeader, Vec<u8>),
+}
+
+/// 可脚本化的模拟RDP服务器
+#[derive(Default)]
+pub struct MockServer {
+    steps: Vec<Step>,
+}
+
+impl MockServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 发送标准的root hello数据包
+    pub fn hello(self) -> Self {
+        self.send(serde_json::json!({
+            "from": "root",
+            "applicationType": "browser",
+            "traits": {},
+        }))
+    }
+
+    /// 等待客户端发来的下一个数据包，`packet` 中的字段必须全部出现在实际数据包中
+    pub fn expect(mut self, packet: Value) -> Self {
+        self.steps.push(Step::Expect(packet));
+        self
+    }
+
+    /// 发送回复，与 `send` 相同，仅用于让脚本更易读
+    pub fn reply(self, packet: Value) -> Self {
+        self.send(packet)
+    }
+
+    /// 立即发送一个数据包
+    pub fn send(mut self, packet: Value) -> Self {
+        self.steps.push(Step::Send(packet));
+        self
+    }
+
+    /// 在后台延迟发送数据包，脚本继续执行后续步骤
+    pub fn send_after(mut self, delay: Duration, packet: Value) -> Self {
+        self.steps.push(Step::SendAfter(delay, packet));
+        self
+    }
+
+    /// 发送bulk数据包
+    pub fn send_bulk(mut self, actor: &str, packet_type: &str, data: Vec<u8>) -> Self {
+        let header = BulkHeader {
+            actor: actor.to_string(),
+            packet_type: packet_type.to_string(),
+            length: data.len() as u64,
+        };
+        self.steps.push(Step::SendBulk(header, data));
+        self
+    }
+
+    /// 暂停脚本
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.steps.push(Step::Delay(delay));
+        self
+    }
+
+    /// 在给定的流上运行脚本
+    pub fn serve<S: AsyncStream + 'static>(self, stream: S) -> MockHandle {
+        MockHandle {
+            task: tokio::spawn(run_script(self.steps, stream)),
+        }
+    }
+
+    /// 在内存duplex上运行脚本，返回供客户端使用的另一端
+    pub fn duplex(self) -> (DuplexStream, MockHandle) {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        (client, self.serve(server))
+    }
+
+    /// 监听本地随机端口，接受一个连接后运行脚本
+    pub async fn listen(self) -> Result<(SocketAddr, MockHandle)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let task = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            run_script(self.steps, stream).await
+        });
+        Ok((addr, MockHandle { task }))
+    }
+}
+
+/// 正在运行的模拟服务器
+pub struct MockHandle {
+    task: JoinHandle<Result<Vec<Value>>>,
+}
+
+impl MockHandle {
+    /// 等待脚本执行完毕，返回收到的所有数据包；任何预期不符都会作为错误返回
+    pub async fn finish(self) -> Result<Vec<Value>> {
+        self.task.await?
+    }
+
+    /// 立即停止模拟服务器并关闭连接
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+}
+
+async fn run_script<S: AsyncStream + 'static>(steps: Vec<Step>, stream: S) -> Result<Vec<Value>> {
+    let (reader, writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let writer_task = tokio::spawn(async move {
+        let mut writer = BufWriter::new(writer);
+        while let Some(outgoing) = rx.recv().await {
+            match outgoing {
+                Outgoing::Json(packet) => protocol::write_json_packet(&mut writer, &packet).await?,
+                Outgoing::Bulk(header, data) => {
+                    protocol::write_bulk_packet(&mut writer, &header, &mut data.as_slice()).await?
+                }
+            }
+        }
+        anyhow::Ok(writer)
+    });
+
+    let mut received = Vec::new();
+    let mut delayed = Vec::new();
+    for step in steps {
+        match step {
+            Step::Expect(expected) => {
+                let actual = loop {
+                    match protocol::read_packet(&mut reader).await? {
+                        Some(Packet::Json(value)) => break value,
+                        Some(Packet::Bulk(header)) => {
+                            protocol::copy_bulk_payload(&mut reader, &header, &mut tokio::io::sink()).await?;
+                        }
+                        None => bail!("connection closed while expecting {}", expected),
+                    }
+                };
+                if !json_contains(&actual, &expected) {
+                    bail!("unexpected packet: expected {}, got {}", expected, actual);
+                }
+                received.push(actual);
+            }
+            Step::Send(packet) => {
+                let _ = tx.send(Outgoing::Json(packet));
+            }
+            Step::SendAfter(delay, packet) => {
+                let tx = tx.clone();
+                delayed.push(tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let _ = tx.send(Outgoing::Json(packet));
+                }));
+            }
+            Step::SendBulk(header, data) => {
+                let _ = tx.send(Outgoing::Bulk(header, data));
+            }
+            Step::Delay(delay) => tokio::time::sleep(delay).await,
+        }
+    }
+
+    for task in delayed {
+        task.await?;
+    }
+    drop(tx);
+    // 保留写半部直到所有数据发送完毕
+    let _writer = writer_task.await??;
+    Ok(received)
+}
+
+/// `expected` 中的每个字段都出现在 `actual` 中且值相同（对象递归比较）
+pub fn json_contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected
+            .iter()
+            .all(|(key, value)| actual.get(key).is_some_and(|actual| json_contains(actual, value))),
+        _ => actual == expected,
+    }
+}
@@ -0,0 +1,197 @@
+use futures::StreamExt;
+use rdp_client::actors::console::ConsoleActor;
+use rdp_client::protocol::ProtocolError;
+use rdp_client::testing::MockServer;
+use rdp_client::{DebugClient, Message};
+use serde_json::json;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+#[tokio::test]
+async fn replies_resolve_requests_in_order() {
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "root", "type": "listTabs"}))
+        .expect(json!({"to": "root", "type": "getRoot"}))
+        .reply(json!({"from": "root", "tabs": []}))
+        .reply(json!({"from": "root", "consoleActor": "console1"}))
+        .duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+
+    let (tabs, root) = tokio::join!(
+        client.request("root", json!({"type": "listTabs"})),
+        client.request("root", json!({"type": "getRoot"})),
+    );
+    assert_eq!(tabs.unwrap().content, json!({"tabs": []}));
+    assert_eq!(root.unwrap().content, json!({"consoleActor": "console1"}));
+    mock.finish().await.unwrap();
+}
+
+#[tokio::test]
+async fn error_replies_become_actor_errors() {
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "tab1", "type": "attach"}))
+        .reply(json!({"from": "tab1", "error": "noSuchActor", "message": "gone"}))
+        .duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+
+    let err = client.request("tab1", json!({"type": "attach"})).await.unwrap_err();
+    match err.downcast_ref::<ProtocolError>() {
+        Some(ProtocolError::ActorError { actor, error, message }) => {
+            assert_eq!(actor, "tab1");
+            assert_eq!(error, "noSuchActor");
+            assert_eq!(message.as_deref(), Some("gone"));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    mock.finish().await.unwrap();
+}
+
+#[tokio::test]
+async fn events_are_not_mistaken_for_replies() {
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "thread1", "type": "interrupt"}))
+        .send(json!({"from": "thread1", "type": "paused", "why": {"type": "interrupted"}}))
+        .delay(Duration::from_millis(20))
+        .reply(json!({"from": "thread1"}))
+        .send_after(Duration::from_millis(20), json!({"from": "thread1", "type": "resumed"}))
+        .duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+    let mut paused = client.subscribe("thread1", "paused");
+    let mut resumed = client.subscribe("thread1", "resumed");
+
+    let reply = client.request("thread1", json!({"type": "interrupt"})).await.unwrap();
+    assert_eq!(reply.packet_type(), None);
+    let event = paused.next().await.unwrap();
+    assert_eq!(event.content["why"]["type"], "interrupted");
+    assert_eq!(resumed.next().await.unwrap().packet_type(), Some("resumed"));
+    mock.finish().await.unwrap();
+}
+
+#[tokio::test]
+async fn receive_message_only_gets_unsubscribed_events() {
+    let (stream, mock) = MockServer::new()
+        .send_after(
+            Duration::from_millis(20),
+            json!({"from": "thread1", "type": "paused", "why": {"type": "interrupted"}}),
+        )
+        .send(json!({"from": "thread1", "type": "resumed"}))
+        .duplex();
+    let mut client = DebugClient::from_stream(stream).unwrap();
+    let mut paused = client.subscribe("thread1", "paused");
+
+    assert_eq!(client.receive_message().await.unwrap().unwrap().packet_type(), Some("resumed"));
+    assert_eq!(paused.next().await.unwrap().packet_type(), Some("paused"));
+    mock.finish().await.unwrap();
+}
+
+#[tokio::test]
+async fn lagging_subscribers_drop_new_events() {
+    let mut mock = MockServer::new();
+    for i in 0..5 {
+        mock = mock.send(json!({"from": "console1", "type": "consoleAPICall", "message": {"n": i}}));
+    }
+    let (stream, mock) = mock.duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+    let mut sub = client.subscribe_with_capacity(Some("console1"), None, 2);
+
+    mock.finish().await.unwrap();
+    let events: Vec<_> = (&mut sub).collect().await;
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].content["message"]["n"], 0);
+    assert_eq!(sub.dropped(), 3);
+}
+
+#[tokio::test]
+async fn bulk_payloads_are_streamed_to_subscribers() {
+    let (addr, mock) = MockServer::new()
+        .hello()
+        .expect(json!({"to": "heap1", "type": "saveHeapSnapshot"}))
+        .send_bulk("heap1", "snapshot", b"0123456789".to_vec())
+        .send(json!({"from": "heap1", "type": "done"}))
+        .listen()
+        .await
+        .unwrap();
+    let mut client = DebugClient::connect("127.0.0.1", addr.port()).await.unwrap();
+    let mut bulk = client.subscribe_bulk(Some("heap1"), None);
+    client
+        .send_message(Message::request("heap1", &json!({"type": "saveHeapSnapshot"})).unwrap())
+        .await
+        .unwrap();
+    assert!(client.receive_message().await.unwrap().unwrap().is_hello());
+
+    let mut packet = bulk.recv().await.unwrap();
+    assert_eq!(packet.header.packet_type, "snapshot");
+    let mut body = Vec::new();
+    packet.body.read_to_end(&mut body).await.unwrap();
+    assert_eq!(body, b"0123456789");
+    assert_eq!(client.receive_message().await.unwrap().unwrap().packet_type(), Some("done"));
+    mock.finish().await.unwrap();
+}
+
+#[tokio::test]
+async fn timed_out_requests_discard_late_replies() {
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "thread1", "type": "frames"}))
+        .expect(json!({"to": "thread1", "type": "sources"}))
+        .reply(json!({"from": "thread1", "frames": []}))
+        .reply(json!({"from": "thread1", "sources": []}))
+        .duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+
+    let err = client
+        .request_with_timeout("thread1", json!({"type": "frames"}), Duration::from_millis(10))
+        .await
+        .unwrap_err();
+    match err.downcast_ref::<ProtocolError>() {
+        Some(ProtocolError::Timeout { actor, packet_type }) => {
+            assert_eq!(actor, "thread1");
+            assert_eq!(packet_type, "frames");
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    let sources = client.request("thread1", json!({"type": "sources"})).await.unwrap();
+    assert_eq!(sources.content, json!({"sources": []}));
+    mock.finish().await.unwrap();
+}
+
+#[tokio::test]
+async fn unanswered_timed_out_requests_stop_swallowing_replies() {
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "thread1", "type": "frames"}))
+        .expect(json!({"to": "thread1", "type": "sources"}))
+        .reply(json!({"from": "thread1", "sources": []}))
+        .duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+
+    let timeout = Duration::from_millis(10);
+    let err = client
+        .request_with_timeout("thread1", json!({"type": "frames"}), timeout)
+        .await
+        .unwrap_err();
+    assert!(matches!(err.downcast_ref::<ProtocolError>(), Some(ProtocolError::Timeout { .. })));
+    // 占位过期后，后续请求收到自己的回复
+    tokio::time::sleep(timeout * 2).await;
+    let sources = client.request("thread1", json!({"type": "sources"})).await.unwrap();
+    assert_eq!(sources.content, json!({"sources": []}));
+    mock.finish().await.unwrap();
+}
+
+#[tokio::test]
+async fn release_only_drops_local_mirrors_of_the_remote_actor() {
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "obj1", "type": "release"}))
+        .reply(json!({"from": "obj1"}))
+        .expect(json!({"to": "obj2", "type": "release"}))
+        .reply(json!({"from": "obj2"}))
+        .duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+    client.registry().register(ConsoleActor::new("obj1".to_string()));
+    let mirror = client.register_mirror("obj2", ConsoleActor::new("console-obj2".to_string()));
+
+    client.release("obj1").await.unwrap();
+    assert!(client.registry().contains("obj1"));
+    client.release("obj2").await.unwrap();
+    assert!(!client.registry().contains(&mirror));
+    mock.finish().await.unwrap();
+}
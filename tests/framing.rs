@@ -0,0 +1,53 @@
+use rdp_client::protocol::{read_packet, read_packet_with_limits, Packet, PacketLimits, ProtocolError};
+
+async fn read_err(input: &[u8], limits: &PacketLimits) -> ProtocolError {
+    let mut reader = input;
+    let err = read_packet_with_limits(&mut reader, limits).await.unwrap_err();
+    err.downcast::<ProtocolError>().unwrap()
+}
+
+#[tokio::test]
+async fn reads_json_and_bulk_packets() {
+    let mut reader: &[u8] = b"12:{\"from\":\"a\"}bulk a heap 3:xyz";
+    match read_packet(&mut reader).await.unwrap() {
+        Some(Packet::Json(value)) => assert_eq!(value["from"], "a"),
+        other => panic!("unexpected packet: {:?}", other),
+    }
+    match read_packet(&mut reader).await.unwrap() {
+        Some(Packet::Bulk(header)) => assert_eq!((header.packet_type.as_str(), header.length), ("heap", 3)),
+        other => panic!("unexpected packet: {:?}", other),
+    }
+    assert_eq!(reader, b"xyz");
+}
+
+#[tokio::test]
+async fn rejects_malformed_and_oversized_headers() {
+    let limits = PacketLimits {
+        max_header_digits: 4,
+        max_json_packet_size: 100,
+        max_bulk_packet_size: 10,
+    };
+    assert!(matches!(read_err(b"12x:{}", &limits).await, ProtocolError::InvalidHeaderByte(b'x')));
+    assert!(matches!(read_err(b"bu:{}", &limits).await, ProtocolError::InvalidHeaderByte(b':')));
+    assert!(matches!(read_err(b":{}", &limits).await, ProtocolError::InvalidHeaderByte(b':')));
+    assert!(matches!(read_err(b"bulk a b 12x:", &limits).await, ProtocolError::InvalidHeaderByte(b'x')));
+    assert!(matches!(read_err(b"bulk a b :", &limits).await, ProtocolError::InvalidHeaderByte(b':')));
+    assert!(matches!(read_err(b"123456", &limits).await, ProtocolError::HeaderTooLong { limit: 4 }));
+    assert!(matches!(
+        read_err(b"101:", &limits).await,
+        ProtocolError::PacketTooLarge { size: 101, limit: 100 }
+    ));
+    assert!(matches!(
+        read_err(b"bulk a heap 11:", &limits).await,
+        ProtocolError::PacketTooLarge { size: 11, limit: 10 }
+    ));
+}
+
+#[tokio::test]
+async fn detects_eof_inside_a_packet() {
+    let limits = PacketLimits::default();
+    assert!(matches!(read_err(b"12", &limits).await, ProtocolError::UnexpectedEof));
+    assert!(matches!(read_err(b"12:{\"a\":", &limits).await, ProtocolError::UnexpectedEof));
+    let mut empty: &[u8] = b"";
+    assert!(read_packet(&mut empty).await.unwrap().is_none());
+}
@@ -0,0 +1,42 @@
+use rdp_client::protocol::ProtocolError;
+use rdp_client::testing::MockServer;
+use rdp_client::DebugClient;
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+
+#[tokio::test]
+async fn connect_parses_server_hello() {
+    let (addr, mock) = MockServer::new()
+        .send(json!({
+            "from": "root",
+            "applicationType": "browser",
+            "traits": {"watcher": true, "networkMonitor": false},
+        }))
+        .listen()
+        .await
+        .unwrap();
+    let client = DebugClient::connect("127.0.0.1", addr.port()).await.unwrap();
+
+    let info = client.server_info().unwrap();
+    assert_eq!(info.application_type, "browser");
+    assert!(client.server_has_trait("watcher"));
+    assert!(!client.server_has_trait("networkMonitor"));
+    assert!(!client.server_has_trait("sources"));
+    drop(client);
+    mock.finish().await.unwrap();
+}
+
+#[tokio::test]
+async fn connect_rejects_non_rdp_peers() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n").await.unwrap();
+    });
+
+    let err = DebugClient::connect("127.0.0.1", port).await.err().unwrap();
+    assert!(matches!(err.downcast_ref::<ProtocolError>(), Some(ProtocolError::NotRdpServer(_))));
+    server.await.unwrap();
+}
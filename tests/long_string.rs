@@ -0,0 +1,63 @@
+use rdp_client::client::{ClientOptions, LONG_STRING_CHUNK};
+use rdp_client::protocol::grip::{Grip, LongStringGrip};
+use rdp_client::testing::MockServer;
+use rdp_client::DebugClient;
+use serde_json::json;
+
+#[tokio::test]
+async fn long_string_fetches_remaining_chunks() {
+    let length = 2 * LONG_STRING_CHUNK + 10;
+    let first = "b".repeat(LONG_STRING_CHUNK as usize);
+    let second = "c".repeat((LONG_STRING_CHUNK - 10) as usize);
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "str1", "type": "substring", "start": 2, "end": 5}))
+        .reply(json!({"from": "str1", "substring": "bbb"}))
+        .expect(json!({"to": "str1", "type": "substring", "start": 20, "end": LONG_STRING_CHUNK + 20}))
+        .reply(json!({"from": "str1", "substring": first.clone()}))
+        .expect(json!({"to": "str1", "type": "substring", "start": LONG_STRING_CHUNK + 20, "end": length}))
+        .reply(json!({"from": "str1", "substring": second.clone()}))
+        .duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+    let long_string = client.long_string(LongStringGrip {
+        actor: "str1".to_string(),
+        initial: "a".repeat(20),
+        length,
+    });
+
+    assert_eq!(long_string.substring(2, 5).await.unwrap(), "bbb");
+    let full = long_string.fetch_full().await.unwrap();
+    assert_eq!(full.len() as u64, length);
+    assert_eq!(full, "a".repeat(20) + &first + &second);
+    mock.finish().await.unwrap();
+}
+
+#[tokio::test]
+async fn long_strings_resolve_up_to_the_configured_limit() {
+    let long_string = |actor: &str, length: u64| {
+        json!({"type": "longString", "actor": actor, "initial": "ab", "length": length})
+    };
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "netEvent1", "type": "getResponseContent"}))
+        .reply(json!({
+            "from": "netEvent1",
+            "content": {"mimeType": "text/plain", "text": long_string("str1", 5)},
+        }))
+        .expect(json!({"to": "str1", "type": "substring", "start": 2, "end": 5}))
+        .reply(json!({"from": "str1", "substring": "cde"}))
+        .expect(json!({"to": "netEvent1", "type": "getRequestPostData"}))
+        .reply(json!({"from": "netEvent1", "postData": {"text": long_string("str2", 500)}}))
+        .duplex();
+    let options = ClientOptions {
+        long_string_limit: Some(100),
+        ..ClientOptions::default()
+    };
+    let client = DebugClient::from_stream_with_options(stream, options).unwrap();
+    let event = client.network_event("netEvent1");
+
+    let content = event.get_response_content().await.unwrap();
+    assert_eq!(content.text, Grip::from("abcde"));
+    assert_eq!(content.mime_type.as_deref(), Some("text/plain"));
+    let post_data = event.get_request_post_data().await.unwrap();
+    assert_eq!(post_data.text.as_long_string().unwrap().length, 500);
+    mock.finish().await.unwrap();
+}
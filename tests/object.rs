@@ -0,0 +1,94 @@
+use rdp_client::protocol::grip::{Grip, ObjectGrip, PropertyDescriptor};
+use rdp_client::protocol::messages::{EnumPropertiesOptions, EvaluationResult};
+use rdp_client::testing::MockServer;
+use rdp_client::DebugClient;
+use serde_json::json;
+
+#[test]
+fn grips_round_trip_special_values() {
+    let values = [
+        (json!({"type": "undefined"}), Grip::Undefined),
+        (json!({"type": "null"}), Grip::Null),
+        (json!({"type": "NaN"}), Grip::NaN),
+        (json!({"type": "-0"}), Grip::NegativeZero),
+        (json!({"type": "-Infinity"}), Grip::NegativeInfinity),
+        (json!({"type": "BigInt", "text": "12"}), Grip::BigInt("12".to_string())),
+        (json!(1.5), Grip::Number(1.5)),
+        (json!("text"), Grip::from("text")),
+    ];
+    for (value, grip) in values {
+        assert_eq!(serde_json::from_value::<Grip>(value.clone()).unwrap(), grip);
+        assert_eq!(serde_json::to_value(&grip).unwrap(), value);
+    }
+    assert_eq!(Grip::from(-0.0), Grip::NegativeZero);
+    assert!(!Grip::NegativeZero.is_truthy() && !Grip::from("").is_truthy());
+    assert!(Grip::from("0").is_truthy() && Grip::BigInt("-1".to_string()).is_truthy());
+
+    let long = json!({"type": "longString", "actor": "str1", "initial": "abc", "length": 100000});
+    let grip: Grip = serde_json::from_value(long.clone()).unwrap();
+    assert_eq!(grip.as_long_string().unwrap().length, 100000);
+    assert_eq!(grip.actor(), Some("str1"));
+    assert_eq!(serde_json::to_value(&grip).unwrap(), long);
+
+    let unknown = json!({"type": "mystery", "actor": "x"});
+    assert_eq!(serde_json::from_value::<Grip>(unknown.clone()).unwrap(), Grip::Other(unknown));
+}
+
+#[tokio::test]
+async fn object_client_walks_properties() {
+    let object = |actor: &str, class: &str| json!({"type": "object", "actor": actor, "class": class});
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "console1", "type": "evaluateJS", "text": "point"}))
+        .reply(json!({"from": "console1", "input": "point", "result": object("obj1", "Object")}))
+        .expect(json!({"to": "obj1", "type": "prototypeAndProperties"}))
+        .reply(json!({
+            "from": "obj1",
+            "prototype": object("proto1", "Object"),
+            "ownProperties": {
+                "x": {"value": 1, "writable": true, "enumerable": true, "configurable": true},
+                "tags": {"value": object("obj2", "Array"), "enumerable": true},
+                "len": {"get": object("fn1", "Function"), "set": {"type": "undefined"}},
+            },
+        }))
+        .expect(json!({"to": "obj2", "type": "ownPropertyNames"}))
+        .reply(json!({"from": "obj2", "ownPropertyNames": ["0", "length"]}))
+        .expect(json!({"to": "obj2", "type": "enumProperties", "options": {"ignoreNonIndexedProperties": true}}))
+        .reply(json!({"from": "obj2", "iterator": {"type": "propertyIterator", "actor": "iter1", "count": 1}}))
+        .expect(json!({"to": "iter1", "type": "slice", "start": 0, "count": 1}))
+        .reply(json!({"from": "iter1", "ownProperties": {"0": {"value": {"type": "null"}}}}))
+        .expect(json!({"to": "obj2", "type": "getPrototype"}))
+        .reply(json!({"from": "obj2", "prototype": object("proto2", "Array")}))
+        .expect(json!({"to": "obj1", "type": "release"}))
+        .reply(json!({"from": "obj1"}))
+        .duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+
+    let evaluation: EvaluationResult = client
+        .request("console1", json!({"type": "evaluateJS", "text": "point"}))
+        .await
+        .unwrap()
+        .parse()
+        .unwrap();
+    let point = client.object(evaluation.result.as_object().unwrap().clone());
+    assert_eq!(point.grip().class, "Object");
+
+    let reply = point.prototype_and_properties().await.unwrap();
+    assert_eq!(reply.prototype.as_object().map(|proto| proto.actor.as_str()), Some("proto1"));
+    assert_eq!(reply.own_properties["x"], PropertyDescriptor::data(Grip::Number(1.0)));
+    assert!(matches!(reply.own_properties["len"].get, Some(Grip::Object(ObjectGrip { .. }))));
+
+    let tags = point.child(reply.own_properties["tags"].value.as_ref().unwrap()).unwrap();
+    assert_eq!(tags.own_property_names().await.unwrap(), ["0", "length"]);
+    let options = EnumPropertiesOptions {
+        ignore_non_indexed_properties: true,
+        ..EnumPropertiesOptions::default()
+    };
+    let iterator = tags.enum_properties(options).await.unwrap();
+    assert_eq!(iterator.count(), 1);
+    assert_eq!(iterator.slice(0, 1).await.unwrap()["0"].value, Some(Grip::Null));
+    assert_eq!(tags.get_prototype().await.unwrap().actor(), Some("proto2"));
+    assert!(point.child(&Grip::Number(2.0)).is_none());
+
+    point.release().await.unwrap();
+    mock.finish().await.unwrap();
+}
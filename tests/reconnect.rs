@@ -0,0 +1,80 @@
+use rdp_client::client::{ClientOptions, ConnectionEvent, Connector, ReconnectPolicy};
+use rdp_client::protocol::thread::Breakpoint;
+use rdp_client::protocol::AsyncStream;
+use rdp_client::testing::{MockHandle, MockServer};
+use rdp_client::DebugClient;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 选中tab并恢复断点、网络监听的服务器脚本；脚本结束后连接关闭
+fn session(thread: &str, location: Value) -> MockServer {
+    MockServer::new()
+        .hello()
+        .expect(json!({"to": "root", "type": "listTabs"}))
+        .reply(json!({
+            "from": "root",
+            "tabs": [
+                {"actor": "tab0", "url": "http://other/"},
+                {"actor": "tab1", "url": "http://example/"},
+            ],
+        }))
+        .expect(json!({"to": "tab1", "type": "getTarget"}))
+        .reply(json!({
+            "from": "tab1",
+            "frame": {"actor": "target1", "consoleActor": "console1", "threadActor": thread},
+        }))
+        .expect(json!({"to": thread, "type": "attach"}))
+        .reply(json!({"from": thread}))
+        .expect(json!({"to": thread, "type": "setBreakpoint", "location": location}))
+        .reply(json!({"from": thread}))
+        .expect(json!({"to": "console1", "type": "startListeners", "listeners": ["NetworkActivity"]}))
+        .reply(json!({"from": "console1", "startedListeners": ["NetworkActivity"]}))
+}
+
+#[tokio::test]
+async fn reconnect_restores_tab_breakpoints_and_listeners() {
+    let (first, first_mock) = session("thread1", json!({"sourceUrl": "http://example/main.js"})).duplex();
+    let (second, second_mock) = session("thread2", json!({"sourceUrl": "http://example/main.js", "line": 3})).duplex();
+    let streams = Arc::new(Mutex::new(VecDeque::from([first, second])));
+    let connector: Connector = Arc::new(move || {
+        let stream = streams.lock().unwrap().pop_front();
+        Box::pin(async move {
+            match stream {
+                Some(stream) => Ok(Box::new(stream) as Box<dyn AsyncStream>),
+                None => anyhow::bail!("server is gone"),
+            }
+        })
+    });
+    let options = ClientOptions {
+        reconnect: Some(ReconnectPolicy {
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            max_attempts: 2,
+        }),
+        ..ClientOptions::default()
+    };
+    let client = DebugClient::from_connector(connector, options).await.unwrap();
+    let mut events = client.connection_events();
+
+    let target = client.select_tab("http://example/").await.unwrap();
+    assert_eq!(target.thread.actor(), "thread1");
+    let breakpoint = Breakpoint::new("http://example/main.js", 3, None);
+    client.set_breakpoint(breakpoint).await.unwrap();
+    client.start_network_listeners().await.unwrap();
+    finish(first_mock).await;
+
+    assert_eq!(events.recv().await.unwrap(), ConnectionEvent::Disconnected);
+    assert_eq!(events.recv().await.unwrap(), ConnectionEvent::Reconnected { attempts: 1 });
+    assert_eq!(client.selected_tab().unwrap().thread_actor, "thread2");
+    finish(second_mock).await;
+
+    assert_eq!(events.recv().await.unwrap(), ConnectionEvent::Disconnected);
+    assert_eq!(events.recv().await.unwrap(), ConnectionEvent::ReconnectFailed);
+    assert!(client.request("root", json!({"type": "listTabs"})).await.is_err());
+}
+
+async fn finish(mock: MockHandle) {
+    mock.finish().await.unwrap();
+}
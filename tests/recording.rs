@@ -0,0 +1,88 @@
+use rdp_client::client::ClientOptions;
+use rdp_client::protocol::recording::{load_records, Direction, Record, RecordedPacket, Recorder, Replay};
+use rdp_client::testing::MockServer;
+use rdp_client::{DebugClient, Message};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+
+#[tokio::test]
+async fn recorded_session_replays_deterministically() {
+    let path = std::env::temp_dir().join(format!("rdp-recording-{}.jsonl", std::process::id()));
+
+    let (stream, mock) = MockServer::new()
+        .hello()
+        .expect(json!({"to": "root", "type": "listTabs"}))
+        .reply(json!({"from": "root", "tabs": [{"actor": "tab1"}]}))
+        .duplex();
+    let options = ClientOptions {
+        recorder: Some(Arc::new(Recorder::create(&path).unwrap())),
+        ..ClientOptions::default()
+    };
+    let mut client = DebugClient::from_stream_with_options(stream, options).unwrap();
+    client.receive_message().await.unwrap().unwrap();
+    let live = client.request("root", json!({"type": "listTabs"})).await.unwrap();
+    mock.finish().await.unwrap();
+    drop(client);
+
+    let records = load_records(&path).unwrap();
+    let directions: Vec<_> = records.iter().map(|r| r.direction).collect();
+    assert_eq!(directions, [Direction::Received, Direction::Sent, Direction::Received]);
+    assert!(records.windows(2).all(|w| w[0].time_us <= w[1].time_us));
+
+    let (stream, replay) = Replay::load(&path).unwrap().strict(true).duplex();
+    let mut client = DebugClient::from_stream(stream).unwrap();
+    let hello = client.receive_message().await.unwrap().unwrap();
+    assert_eq!(hello.content["applicationType"], "browser");
+    let replayed = client.request("root", json!({"type": "listTabs"})).await.unwrap();
+    assert_eq!(replayed.content, live.content);
+    replay.await.unwrap().unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn recorded_bulk_payloads_are_replayed() {
+    let path = std::env::temp_dir().join(format!("rdp-recording-bulk-{}.jsonl", std::process::id()));
+    let request = || Message::request("heap1", &json!({"type": "saveHeapSnapshot"})).unwrap();
+
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "heap1", "type": "saveHeapSnapshot"}))
+        .send_bulk("heap1", "snapshot", b"0123456789".to_vec())
+        .duplex();
+    let options = ClientOptions {
+        recorder: Some(Arc::new(Recorder::create(&path).unwrap())),
+        ..ClientOptions::default()
+    };
+    let client = DebugClient::from_stream_with_options(stream, options).unwrap();
+    let mut bulk = client.subscribe_bulk(Some("heap1"), None);
+    client.send_message(request()).await.unwrap();
+    bulk.recv().await.unwrap().body.read_to_end(&mut Vec::new()).await.unwrap();
+    mock.finish().await.unwrap();
+    drop(client);
+
+    let (stream, replay) = Replay::load(&path).unwrap().strict(true).duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+    let mut bulk = client.subscribe_bulk(Some("heap1"), None);
+    client.send_message(request()).await.unwrap();
+    let mut body = Vec::new();
+    bulk.recv().await.unwrap().body.read_to_end(&mut body).await.unwrap();
+    assert_eq!(body, b"0123456789");
+    replay.await.unwrap().unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn recordings_without_bulk_payloads_are_rejected() {
+    let record = Record {
+        direction: Direction::Received,
+        time_us: 0,
+        packet: RecordedPacket::Bulk {
+            actor: "heap1".to_string(),
+            packet_type: "snapshot".to_string(),
+            length: 10,
+            body: None,
+        },
+    };
+    let (_stream, replay) = Replay::new(vec![record]).duplex();
+    assert!(replay.await.unwrap().is_err());
+}
@@ -0,0 +1,513 @@
+use rdp_client::actors::console::ConsoleActor;
+use rdp_client::actors::debugger::{
+    BreakpointHit, DebuggeeRuntime, DebuggerActor, ExpressionEvaluator, FrameState, ResumeOutcome,
+};
+use rdp_client::actors::source::SourceActor;
+use rdp_client::actors::tab::TabActor;
+use rdp_client::actors::ActorRegistry;
+use rdp_client::client::Target;
+use rdp_client::protocol::grip::{Grip, PropertyDescriptor};
+use rdp_client::protocol::messages::{
+    PauseReason, PositionQuery, QueryPosition, ReloadOptions, ResumeLimit, ThreadEvent,
+};
+use rdp_client::protocol::thread::{
+    Bindings, Breakpoint, Environment, EnvironmentType, Frame, FrameType, Location,
+};
+use rdp_client::protocol::ProtocolError;
+use rdp_client::{DebugClient, DevToolsServer};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[tokio::test]
+async fn server_sends_hello_and_dispatches_by_actor() {
+    let server = DevToolsServer::bind("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(server.run());
+
+    let mut client = DebugClient::connect("127.0.0.1", addr.port()).await.unwrap();
+    let hello = client.receive_message().await.unwrap().unwrap();
+    assert_eq!(hello.from, "root");
+    assert_eq!(hello.content["applicationType"], "browser");
+
+    let tabs = client.request("root", json!({"type": "listTabs"})).await.unwrap();
+    assert_eq!(tabs.content["tabs"], json!([]));
+
+    let err = client.request("missing", json!({"type": "attach"})).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ProtocolError>(),
+        Some(ProtocolError::ActorError { error, .. }) if error == "noSuchActor"
+    ));
+
+    // 所有连接共享的root actor不能被释放
+    let err = client.request("root", json!({"type": "release"})).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ProtocolError>(),
+        Some(ProtocolError::ActorError { error, .. }) if error == "cannotRelease"
+    ));
+    assert!(client.request("root", json!({"type": "listTabs"})).await.is_ok());
+}
+
+#[tokio::test]
+async fn root_actor_lists_registered_tabs() {
+    let server = DevToolsServer::bind("127.0.0.1:0").await.unwrap();
+    server.registry().register(TabActor::new(
+        "tab1".to_string(),
+        "Example".to_string(),
+        "http://example/".to_string(),
+    ));
+    server
+        .registry()
+        .register(TabActor::new("tab2".to_string(), "Other".to_string(), "http://other/".to_string()).with_browser_id(7));
+    let registry = server.registry().clone();
+    registry.register(ConsoleActor::new("console1".to_string()));
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(server.run());
+
+    let client = DebugClient::connect("127.0.0.1", addr.port()).await.unwrap();
+    // 其他actor正忙时listTabs不需要等待它
+    let busy = registry.get::<ConsoleActor>("console1").await.unwrap();
+    let tabs = tokio::time::timeout(Duration::from_secs(1), client.list_tabs()).await.unwrap().unwrap();
+    drop(busy);
+    let urls: Vec<_> = tabs.iter().map(|tab| tab.url.as_str()).collect();
+    assert_eq!(urls, ["http://example/", "http://other/"]);
+
+    let tab = client.get_tab(Some(7)).await.unwrap();
+    assert_eq!(tab.actor, "tab2");
+    assert!(client.get_tab(Some(8)).await.is_err());
+    assert!(client.list_workers().await.unwrap().is_empty());
+}
+
+/// `setup_tab` 中tab的线程actor
+const THREAD: &str = "thread1";
+
+/// 启动只有一个tab（`tab1`、`console1`、`thread1`）的服务器，连接客户端并选中该tab
+///
+/// `evaluator` 和 `runtime` 交给线程的 `DebuggerActor`，日志点消息由 `console1` 发出。
+async fn setup_tab(
+    evaluator: Option<Arc<dyn ExpressionEvaluator>>,
+    runtime: Option<Arc<dyn DebuggeeRuntime>>,
+) -> (ActorRegistry, DebugClient, Target) {
+    let server = DevToolsServer::bind("127.0.0.1:0").await.unwrap();
+    let registry = server.registry().clone();
+    let console = registry.register(ConsoleActor::new("console1".to_string()));
+    let mut debugger = DebuggerActor::new(THREAD.to_string()).with_console(&console);
+    if let Some(evaluator) = evaluator {
+        debugger = debugger.with_evaluator(evaluator);
+    }
+    if let Some(runtime) = runtime {
+        debugger = debugger.with_runtime(runtime);
+    }
+    let thread = registry.register(debugger);
+    registry.register(
+        TabActor::new("tab1".to_string(), "Example".to_string(), "http://example/".to_string())
+            .with_actors(console, thread),
+    );
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(server.run());
+
+    let client = DebugClient::connect("127.0.0.1", addr.port()).await.unwrap();
+    let target = client.select_tab("http://example/").await.unwrap();
+    (registry, client, target)
+}
+
+#[tokio::test]
+async fn select_tab_builds_console_and_thread_handles() {
+    let (_, client, target) = setup_tab(None, None).await;
+    assert_eq!(target.actor(), "tab1");
+    assert_eq!(target.thread.actor(), "thread1");
+    assert_eq!(client.selected_tab().unwrap().console_actor, "console1");
+
+    let started = target.console.start_listeners(&["PageError"]).await.unwrap();
+    assert!(started.native_console_api);
+    let evaluation = target.console.evaluate_js("1 + 1").await.unwrap();
+    assert_eq!(evaluation.result, Grip::Undefined);
+    target.thread.detach().await.unwrap();
+}
+
+#[tokio::test]
+async fn tab_navigation_emits_events_and_updates_url() {
+    let (_, client, target) = setup_tab(None, None).await;
+    let mut will_navigate = client.subscribe("tab1", "will-navigate");
+
+    let loaded = target.navigate_to("http://second/").await.unwrap();
+    assert_eq!(loaded.url, "http://second/");
+    assert_eq!(will_navigate.recv().await.unwrap().content["newURI"], "http://second/");
+    assert_eq!(client.selected_tab().unwrap().url, "http://second/");
+    assert_eq!(client.list_tabs().await.unwrap()[0].url, "http://second/");
+
+    assert_eq!(target.go_back().await.unwrap().url, "http://example/");
+    let reloaded = target.reload(true).await.unwrap();
+    assert_eq!((reloaded.url.as_str(), reloaded.reload), ("http://example/", Some(ReloadOptions { force: true })));
+    will_navigate.recv().await.unwrap();
+    assert_eq!(will_navigate.recv().await.unwrap().content["reload"], json!({"force": true}));
+    let forward = target.go_forward().await.unwrap();
+    assert_eq!((forward.url.as_str(), forward.reload), ("http://second/", None));
+    assert!(target.go_forward().await.is_err());
+    let detached = client.request("tab1", json!({"type": "detach"})).await.unwrap();
+    assert_eq!(detached.content, json!({}));
+}
+
+fn frame_state(actor: &str, callee: Option<&str>, line: u32, environment: Option<Environment>) -> FrameState {
+    FrameState {
+        frame: Frame {
+            actor: actor.to_string(),
+            frame_type: if callee.is_some() { FrameType::Call } else { FrameType::Global },
+            callee: callee.map(str::to_string),
+            location: Location {
+                line,
+                column: 0,
+                actor: "source1".to_string(),
+            },
+            this: Grip::Undefined,
+        },
+        environment,
+    }
+}
+
+#[tokio::test]
+async fn paused_thread_reports_frames_and_scopes() {
+    let (registry, client, target) = setup_tab(None, None).await;
+    let mut paused = target.thread.subscribe("paused");
+    let mut resumed = target.thread.subscribe("resumed");
+    assert!(target.thread.frames(0, 10).await.is_err());
+
+    target.thread.interrupt().await.unwrap();
+    assert_eq!(paused.recv().await.unwrap().content["why"]["type"], "interrupted");
+    target.thread.resume().await.unwrap();
+    resumed.recv().await.unwrap();
+
+    let object = |actor: &str, class: &str| {
+        serde_json::from_value::<Grip>(json!({"type": "object", "actor": actor, "class": class})).unwrap()
+    };
+    let (window, function) = (object("window1", "Window"), object("fn1", "Function"));
+    let global = Environment {
+        actor: "env-global".to_string(),
+        env_type: EnvironmentType::Object,
+        function: None,
+        object: Some(window),
+        bindings: None,
+        parent: None,
+    };
+    let local = Environment {
+        actor: "env-local".to_string(),
+        env_type: EnvironmentType::Function,
+        function: Some(function),
+        object: None,
+        bindings: Some(Bindings {
+            arguments: vec![[("a".to_string(), PropertyDescriptor::data(Grip::Number(1.0)))].into()],
+            variables: [("sum".to_string(), PropertyDescriptor::data(Grip::Number(3.0)))].into(),
+        }),
+        parent: Some(Box::new(global)),
+    };
+    registry.get::<DebuggerActor>(THREAD).await.unwrap().pause(
+        PauseReason::Breakpoint { actors: Vec::new() },
+        vec![
+            frame_state("frame1", Some("add"), 3, Some(local.clone())),
+            frame_state("frame2", None, 10, None),
+        ],
+    );
+    let event = paused.recv().await.unwrap();
+    assert_eq!(event.content["frame"]["displayName"], "add");
+    assert!(target.thread.step_over().await.is_err());
+
+    let frames = target.thread.frames(1, 1).await.unwrap();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].frame_type, FrameType::Global);
+    assert_eq!(frames[0].location.line, 10);
+    assert!(target.thread.get_environment(&frames[0]).await.is_err());
+
+    let top = &target.thread.frames(0, 1).await.unwrap()[0];
+    let environment = target.thread.get_environment(top).await.unwrap();
+    assert_eq!(environment, local);
+    let scopes: Vec<_> = environment.scopes().map(|scope| scope.env_type).collect();
+    assert_eq!(scopes, [EnvironmentType::Function, EnvironmentType::Object]);
+
+    // 帧属于actor池，可以释放；thread actor不能
+    assert!(client.release(THREAD).await.is_err());
+    client.release("frame2").await.unwrap();
+    assert!(!registry.contains("frame2") && registry.contains(THREAD));
+
+    target.thread.resume().await.unwrap();
+    resumed.recv().await.unwrap();
+    assert!(!registry.contains("frame1"));
+}
+
+#[tokio::test]
+async fn thread_lists_sources_and_serves_source_text() {
+    let (registry, client, target) = setup_tab(None, None).await;
+    let mut new_sources = target.thread.subscribe("newSource");
+    let text = "// add\nfunction add(a, b) {\n  return a + b;\n}\n";
+    registry.get::<DebuggerActor>(THREAD).await.unwrap().add_source(SourceActor::new(
+        "source1".to_string(),
+        Some("http://example/main.js".to_string()),
+        text.to_string(),
+    ));
+    new_sources.recv().await.unwrap();
+    let source = client.find_source("http://example/main.js").unwrap();
+    assert_eq!(source.actor(), "source1");
+
+    let sources = target.thread.sources().await.unwrap();
+    assert_eq!(sources.len(), 1);
+    assert_eq!(source.source().await.unwrap().text, text);
+    assert_eq!(source.get_breakable_lines().await.unwrap(), [2, 3, 4]);
+    let query = PositionQuery {
+        start: QueryPosition { line: 3, column: 0 },
+        end: QueryPosition { line: 4, column: 0 },
+    };
+    let positions = source.get_breakpoint_positions_compressed(Some(query)).await.unwrap();
+    assert_eq!(positions, [(3, vec![2])].into());
+
+    let location = Location {
+        line: 3,
+        column: 2,
+        actor: "source1".to_string(),
+    };
+    let lines = client.source_lines(&location, 1).await.unwrap();
+    assert_eq!(lines.iter().map(|(line, _)| *line).collect::<Vec<_>>(), [2, 3, 4]);
+    assert_eq!(lines[1].1, "  return a + b;");
+
+    // 导航后旧页面的源码不再可用
+    target.navigate_to("http://second/").await.unwrap();
+    assert!(client.find_source("http://example/main.js").is_none());
+    assert!(client.find_source("source1").is_none() && client.cached_sources().is_empty());
+}
+
+#[tokio::test]
+async fn url_breakpoints_slide_and_apply_to_new_sources() {
+    let (registry, client, _) = setup_tab(None, None).await;
+    let url = "http://example/main.js";
+    let text = "function f() {\n\n  return 1;\n}\n";
+
+    // 源码尚未加载时断点处于等待状态
+    let pending = Breakpoint::new(url, 2, None);
+    assert!(client.set_breakpoint(pending.clone()).await.unwrap().actual_locations.is_empty());
+
+    let source = |name: &str| SourceActor::new(name.to_string(), Some(url.to_string()), text.to_string());
+    registry.get::<DebuggerActor>(THREAD).await.unwrap().add_source(source("source1"));
+    let actual = registry.get::<DebuggerActor>(THREAD).await.unwrap().actual_locations(&pending.location);
+    assert_eq!((actual[0].line, actual[0].column), (3, 2));
+
+    // 导航后加载的同URL源码同样生效
+    registry.get::<DebuggerActor>(THREAD).await.unwrap().add_source(source("source2"));
+    let actual = client.set_breakpoint(Breakpoint::new(url, 1, Some(5))).await.unwrap().actual_locations;
+    let sources: Vec<_> = actual.iter().map(|location| location.actor.as_str()).collect();
+    assert_eq!(sources, ["source1", "source2"]);
+    assert!(actual.iter().all(|location| (location.line, location.column) == (3, 2)));
+
+    let error = client.set_breakpoint(Breakpoint::new(url, 5, None)).await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ProtocolError>(),
+        Some(ProtocolError::ActorError { error, .. }) if error == "noBreakablePosition"
+    ));
+    client.remove_breakpoint(&pending.location).await.unwrap();
+    assert!(client.remove_breakpoint(&pending.location).await.is_err());
+    let local = client.actor::<DebuggerActor>(client.debugger_actor()).await.unwrap();
+    assert_eq!(local.breakpoints().count(), 1);
+}
+
+#[tokio::test]
+async fn conditional_breakpoints_and_logpoints_use_the_host_evaluator() {
+    let evaluator = |_: Option<&FrameState>, expression: &str| match expression {
+        "x" => Ok(Grip::Number(42.0)),
+        "ready" => Ok(Grip::Bool(true)),
+        "missing" => Ok(Grip::Undefined),
+        _ => Err(format!("ReferenceError: {} is not defined", expression)),
+    };
+    let (registry, client, target) = setup_tab(Some(Arc::new(evaluator)), None).await;
+    let mut paused = target.thread.subscribe("paused");
+    let mut logs = target.console.subscribe("consoleAPICall");
+    let url = "http://example/main.js";
+    let text = "let x = 42;\nfoo(x);\n";
+    let source = SourceActor::new("source1".to_string(), Some(url.to_string()), text.to_string());
+    registry.get::<DebuggerActor>(THREAD).await.unwrap().add_source(source);
+    let at = |line: u32| Location {
+        line,
+        column: 0,
+        actor: "source1".to_string(),
+    };
+    let hit = |line: u32| {
+        let registry = registry.clone();
+        async move { registry.get::<DebuggerActor>(THREAD).await.unwrap().hit_breakpoint(&at(line), Vec::new()) }
+    };
+
+    let conditional = Breakpoint::new(url, 1, None).with_condition("missing");
+    let missing = client.set_breakpoint(conditional.clone()).await.unwrap();
+    assert_eq!(hit(1).await, BreakpointHit::Skipped);
+    let ready = client.set_breakpoint(conditional.clone().with_condition("ready")).await.unwrap();
+    assert_eq!(ready.actor, missing.actor);
+    // 滑动到同一位置的另一个断点
+    let column = client.set_breakpoint(Breakpoint::new(url, 1, Some(0))).await.unwrap();
+    assert_eq!(hit(1).await, BreakpointHit::Paused);
+    let why = paused.recv().await.unwrap().content["why"].clone();
+    assert_eq!(why, json!({"type": "breakpoint", "actors": [ready.actor, column.actor]}));
+    target.thread.resume().await.unwrap();
+    let column = column.actor.unwrap();
+    assert_eq!(client.request(&column, json!({"type": "delete"})).await.unwrap().content, json!({}));
+    assert!(!registry.contains(&column));
+    let remaining: Vec<_> = registry.get::<DebuggerActor>(THREAD).await.unwrap().breakpoints().cloned().collect();
+    assert_eq!(remaining, [conditional.clone().with_condition("ready")]);
+
+    client.set_breakpoint(conditional.clone().with_condition("oops")).await.unwrap();
+    assert_eq!(hit(1).await, BreakpointHit::Paused);
+    let why = paused.recv().await.unwrap().content["why"].clone();
+    assert_eq!(why["type"], "breakpointConditionThrown");
+    target.thread.resume().await.unwrap();
+
+    let logpoint = Breakpoint::new(url, 2, None).with_log_value("x");
+    client.set_breakpoint(logpoint.clone()).await.unwrap();
+    assert_eq!(hit(2).await, BreakpointHit::Logged);
+    let message = logs.recv().await.unwrap().content["message"].clone();
+    assert_eq!((message["level"].clone(), message["arguments"].clone()), (json!("logPoint"), json!([42.0])));
+    assert_eq!(hit(3).await, BreakpointHit::NoBreakpoint);
+
+    let debugger = registry.get::<DebuggerActor>(THREAD).await.unwrap();
+    assert_eq!(debugger.hit_count(&conditional.location), 2);
+    assert_eq!(debugger.hit_count(&logpoint.location), 1);
+    drop(debugger);
+
+    // 移除后在同一位置重新设置的断点从零开始计数
+    client.remove_breakpoint(&conditional.location).await.unwrap();
+    client.set_breakpoint(conditional.clone()).await.unwrap();
+    let debugger = registry.get::<DebuggerActor>(THREAD).await.unwrap();
+    assert_eq!(debugger.hit_count(&conditional.location), 0);
+}
+
+#[tokio::test]
+async fn exceptions_pause_according_to_thread_options() {
+    let (registry, _client, target) = setup_tab(None, None).await;
+    let mut paused = target.thread.subscribe("paused");
+    let throw = |caught: bool| {
+        let registry = registry.clone();
+        async move {
+            let mut debugger = registry.get::<DebuggerActor>(THREAD).await.unwrap();
+            debugger.throw_exception(Grip::from("boom"), caught, Vec::new())
+        }
+    };
+
+    assert!(!throw(false).await);
+    target.thread.pause_on_exceptions(true, true).await.unwrap();
+    let options = registry.get::<DebuggerActor>(THREAD).await.unwrap().options().clone();
+    assert!(options.pause_on_exceptions && options.ignore_caught_exceptions);
+    // 格式错误的选项被拒绝，已有配置保持不变
+    let legacy = json!({"type": "pauseOnExceptions", "pauseOnExceptions": "yes"});
+    let reconfigure = json!({"type": "reconfigure", "options": {"ignoreCaughtExceptions": 1}});
+    for packet in [legacy, reconfigure] {
+        let error = target.thread.request(packet).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ProtocolError>(),
+            Some(ProtocolError::ActorError { error, .. }) if error == "wrongParameter"
+        ));
+    }
+    assert_eq!(registry.get::<DebuggerActor>(THREAD).await.unwrap().options(), &options);
+    assert!(!throw(true).await);
+    assert!(throw(false).await);
+    match paused.recv().await.unwrap().parse::<ThreadEvent>().unwrap() {
+        ThreadEvent::Paused { actor, why: PauseReason::Exception { exception }, .. } => {
+            assert_eq!(actor, None);
+            assert_eq!(exception, Grip::from("boom"));
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    target.thread.resume().await.unwrap();
+
+    target.thread.interrupt().await.unwrap();
+    let event = paused.recv().await.unwrap().parse::<ThreadEvent>().unwrap();
+    assert!(matches!(event, ThreadEvent::Paused { why: PauseReason::Interrupted { on_next: false }, .. }));
+}
+
+#[tokio::test]
+async fn partial_reconfigures_merge_into_thread_options() {
+    let (registry, _client, target) = setup_tab(None, None).await;
+    let options = || async { registry.get::<DebuggerActor>(THREAD).await.unwrap().options().clone() };
+
+    let ignore = json!({"type": "reconfigure", "options": {"ignoreCaughtExceptions": true}});
+    target.thread.request(ignore).await.unwrap();
+    let pause = json!({"type": "reconfigure", "options": {"pauseOnExceptions": true}});
+    target.thread.request(pause).await.unwrap();
+    let merged = options().await;
+    assert!(merged.pause_on_exceptions && merged.ignore_caught_exceptions);
+
+    let stop = json!({"type": "reconfigure", "options": {"pauseOnExceptions": false}});
+    target.thread.request(stop).await.unwrap();
+    let merged = options().await;
+    assert!(!merged.pause_on_exceptions && merged.ignore_caught_exceptions);
+}
+
+/// 只记录调用栈的玩具解释器：单步跳过前进一行，单步进入压入新帧，单步跳出弹出栈顶
+#[derive(Default)]
+struct ToyRuntime {
+    calls: Mutex<Vec<Option<ResumeLimit>>>,
+}
+
+impl DebuggeeRuntime for ToyRuntime {
+    fn resume(&self, limit: Option<ResumeLimit>, frames: &[FrameState]) -> Result<ResumeOutcome, String> {
+        self.calls.lock().unwrap().push(limit);
+        let top = &frames[0].frame;
+        let mut frames = frames.to_vec();
+        match limit {
+            None => return Ok(ResumeOutcome::Running),
+            Some(ResumeLimit::Next) => {
+                let line = top.location.line + 1;
+                frames[0] = frame_state(&format!("frame{}", line), top.callee.as_deref(), line, None);
+            }
+            Some(ResumeLimit::Step) => frames.insert(0, frame_state("frame20", Some("inner"), 20, None)),
+            Some(ResumeLimit::Finish) if frames.len() > 1 => {
+                frames.remove(0);
+            }
+            Some(limit) => return Err(format!("cannot {:?} here", limit)),
+        }
+        Ok(ResumeOutcome::Paused(frames))
+    }
+
+    fn restart_frame(&self, index: usize, frames: &[FrameState]) -> Result<ResumeOutcome, String> {
+        self.calls.lock().unwrap().push(Some(ResumeLimit::Restart));
+        let frame = &frames[index].frame;
+        let mut frames = frames[index..].to_vec();
+        frames[0] = frame_state("frame1", frame.callee.as_deref(), 1, None);
+        Ok(ResumeOutcome::Paused(frames))
+    }
+}
+
+#[tokio::test]
+async fn stepping_is_delegated_to_the_debuggee_runtime() {
+    let runtime = Arc::new(ToyRuntime::default());
+    let (registry, _client, target) = setup_tab(None, Some(runtime.clone())).await;
+    let mut paused = target.thread.subscribe("paused");
+    let mut next_pause = async || match paused.recv().await.unwrap().parse::<ThreadEvent>().unwrap() {
+        ThreadEvent::Paused { frame, why, .. } => (frame.unwrap(), why),
+        other => panic!("unexpected event {:?}", other),
+    };
+    registry.get::<DebuggerActor>(THREAD).await.unwrap().pause(
+        PauseReason::DebuggerStatement,
+        vec![frame_state("frame3", Some("add"), 3, None), frame_state("frame10", None, 10, None)],
+    );
+    assert_eq!(next_pause().await.1, PauseReason::DebuggerStatement);
+
+    target.thread.step_over().await.unwrap();
+    let (frame, why) = next_pause().await;
+    assert_eq!((frame.location.line, why), (4, PauseReason::ResumeLimit));
+    assert!(!registry.contains("frame3"));
+
+    target.thread.step_in().await.unwrap();
+    assert_eq!(next_pause().await.0.callee.as_deref(), Some("inner"));
+    target.thread.step_out().await.unwrap();
+    assert_eq!(next_pause().await.0.actor, "frame4");
+
+    let frames = target.thread.frames(0, 2).await.unwrap();
+    target.thread.restart_frame(&frames[1]).await.unwrap();
+    let frame = next_pause().await.0;
+    assert_eq!((frame.frame_type, frame.location.line), (FrameType::Global, 1));
+
+    let error = target.thread.step_out().await.unwrap_err();
+    assert!(error.to_string().contains("runtimeError"), "{}", error);
+    assert!(registry.get::<DebuggerActor>(THREAD).await.unwrap().is_paused());
+    let error = target.thread.restart_frame(&frames[0]).await.unwrap_err();
+    assert!(error.to_string().contains("unknownFrame"), "{}", error);
+
+    target.thread.resume().await.unwrap();
+    assert!(!registry.get::<DebuggerActor>(THREAD).await.unwrap().is_paused());
+    use ResumeLimit::*;
+    let calls = runtime.calls.lock().unwrap().clone();
+    assert_eq!(calls, [Some(Next), Some(Step), Some(Finish), Some(Restart), Some(Finish), None]);
+}
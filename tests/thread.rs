@@ -0,0 +1,133 @@
+use rdp_client::protocol::grip::Grip;
+use rdp_client::protocol::messages::{FramesReply, PauseReason, ThreadEvent};
+use rdp_client::protocol::thread::{FrameType, Location};
+use rdp_client::Message;
+use serde_json::json;
+
+/// 按Firefox服务器的格式构造的 `frames` 回复，包含本库不使用的字段
+fn firefox_frames_packet() -> serde_json::Value {
+    json!({
+        "from": "server1.conn0.child2/thread1",
+        "frames": [
+            {
+                "actor": "server1.conn0.child2/frame4",
+                "type": "call",
+                "arguments": [1, 2],
+                "asyncCause": null,
+                "state": "on-stack",
+                "oldest": false,
+                "displayName": "add",
+                "this": {
+                    "type": "object",
+                    "actor": "server1.conn0.child2/obj23",
+                    "class": "Window",
+                    "ownPropertyLength": 829,
+                    "extensible": true,
+                    "frozen": false,
+                    "sealed": false,
+                    "isError": false,
+                    "preview": {"kind": "ObjectWithURL", "url": "http://example.com/"}
+                },
+                "where": {"actor": "server1.conn0.child2/source19", "line": 3, "column": 2}
+            },
+            {
+                "actor": "server1.conn0.child2/frame5",
+                "type": "global",
+                "arguments": [],
+                "asyncCause": null,
+                "state": "on-stack",
+                "oldest": true,
+                "this": {
+                    "type": "object",
+                    "actor": "server1.conn0.child2/obj23",
+                    "class": "Window",
+                    "ownPropertyLength": 829,
+                    "extensible": true,
+                    "frozen": false,
+                    "sealed": false,
+                    "isError": false
+                },
+                "where": {"actor": "server1.conn0.child2/source19", "line": 10, "column": 0}
+            }
+        ]
+    })
+}
+
+#[test]
+fn parses_firefox_frames_reply() {
+    let msg: Message = serde_json::from_value(firefox_frames_packet()).unwrap();
+    let reply: FramesReply = msg.parse().unwrap();
+    assert_eq!(reply.frames.len(), 2);
+
+    let top = &reply.frames[0];
+    assert_eq!((top.frame_type, top.callee.as_deref()), (FrameType::Call, Some("add")));
+    assert_eq!(
+        top.location,
+        Location {
+            actor: "server1.conn0.child2/source19".to_string(),
+            line: 3,
+            column: 2,
+        }
+    );
+    assert_eq!(top.this.as_object().map(|this| this.class.as_str()), Some("Window"));
+
+    let global = &reply.frames[1];
+    assert_eq!((global.frame_type, global.callee.as_deref()), (FrameType::Global, None));
+    assert!(matches!(global.this, Grip::Object(_)));
+    assert_eq!(serde_json::to_value(&global.location).unwrap()["actor"], "server1.conn0.child2/source19");
+}
+
+#[test]
+fn parses_firefox_exception_pause() {
+    // 按Firefox服务器的格式构造：在未捕获的异常处暂停时推送的事件
+    let packet = json!({
+        "from": "server1.conn0.child2/thread1",
+        "type": "paused",
+        "frame": {
+            "actor": "server1.conn0.child2/frame7",
+            "type": "call",
+            "arguments": [],
+            "asyncCause": null,
+            "state": "on-stack",
+            "oldest": false,
+            "displayName": "fail",
+            "this": {"type": "undefined"},
+            "where": {"actor": "server1.conn0.child2/source19", "line": 14, "column": 8}
+        },
+        "why": {
+            "type": "exception",
+            "exception": {
+                "type": "object",
+                "actor": "server1.conn0.child2/obj31",
+                "class": "Error",
+                "ownPropertyLength": 4,
+                "extensible": true,
+                "frozen": false,
+                "sealed": false,
+                "isError": true,
+                "preview": {
+                    "kind": "Error",
+                    "name": "Error",
+                    "message": "boom",
+                    "stack": "fail@http://example.com/main.js:14:9\n@http://example.com/main.js:20:1\n",
+                    "fileName": "http://example.com/main.js",
+                    "lineNumber": 14,
+                    "columnNumber": 9
+                }
+            }
+        }
+    });
+    let msg: Message = serde_json::from_value(packet).unwrap();
+    let ThreadEvent::Paused { actor, frame, why } = msg.parse().unwrap() else {
+        panic!("not a paused event");
+    };
+    assert_eq!(actor, None);
+    let frame = frame.unwrap();
+    assert_eq!((frame.callee.as_deref(), frame.location.line), (Some("fail"), 14));
+    let PauseReason::Exception { exception } = why else {
+        panic!("unexpected pause reason {:?}", why);
+    };
+    let error = exception.as_object().unwrap();
+    assert_eq!((error.actor.as_str(), error.class.as_str()), ("server1.conn0.child2/obj31", "Error"));
+    assert_eq!(error.preview.as_ref().unwrap()["message"], "boom");
+}
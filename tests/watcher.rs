@@ -0,0 +1,61 @@
+use rdp_client::client::ResourceChange;
+use rdp_client::protocol::messages::{ConsoleMessageResource, ResourceType, TabDescriptor};
+use rdp_client::testing::MockServer;
+use rdp_client::DebugClient;
+use serde_json::json;
+
+#[tokio::test]
+async fn watcher_streams_typed_resources() {
+    let (stream, mock) = MockServer::new()
+        .expect(json!({"to": "tab1", "type": "getWatcher"}))
+        .reply(json!({
+            "from": "tab1",
+            "actor": "watcher1",
+            "traits": {"frame": true, "resources": {"console-message": true}},
+        }))
+        .expect(json!({"to": "watcher1", "type": "watchTargets", "targetType": "frame"}))
+        .send(json!({
+            "from": "watcher1",
+            "type": "target-available-form",
+            "target": {"actor": "target1", "consoleActor": "console1", "threadActor": "thread1"},
+        }))
+        .reply(json!({"from": "watcher1"}))
+        .expect(json!({"to": "watcher1", "type": "watchResources", "resourceTypes": ["console-message"]}))
+        .send(json!({
+            "from": "watcher1",
+            "type": "resource-available-array",
+            "array": [
+                ["console-message", [{"level": "log", "arguments": ["cached"]}]],
+                ["stylesheet", [{"href": "a.css"}]],
+            ],
+        }))
+        .reply(json!({"from": "watcher1"}))
+        .send(json!({
+            "from": "target1",
+            "type": "resource-available-form",
+            "resources": [{"resourceType": "console-message", "level": "warn", "arguments": ["live"]}],
+        }))
+        .duplex();
+    let client = DebugClient::from_stream(stream).unwrap();
+    let descriptor: TabDescriptor = serde_json::from_value(json!({"actor": "tab1"})).unwrap();
+
+    let watcher = client.get_watcher(&descriptor).await.unwrap();
+    assert!(watcher.supports_resource(&ResourceType::ConsoleMessage));
+    assert!(!watcher.supports_resource(&ResourceType::Source));
+
+    let targets = watcher.watch_targets("frame").await.unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].thread_actor, "thread1");
+
+    let mut resources = watcher.watch_resources(&[ResourceType::ConsoleMessage]).await.unwrap();
+    let cached = resources.recv().await.unwrap();
+    assert_eq!(cached.change, ResourceChange::Available);
+    assert_eq!(cached.from, "watcher1");
+    let message: ConsoleMessageResource = cached.resource.parse().unwrap();
+    assert_eq!(message.arguments, [json!("cached")]);
+
+    let live = resources.recv().await.unwrap();
+    assert_eq!(live.from, "target1");
+    assert_eq!(live.resource.parse::<ConsoleMessageResource>().unwrap().level.as_deref(), Some("warn"));
+    mock.finish().await.unwrap();
+}